@@ -0,0 +1,386 @@
+//! JSON API for the arithmetic operations, mounted under `/api`.
+//!
+//! Numbers may be given as JSON numbers, or as strings in decimal or
+//! `0x`-prefixed hexadecimal for those beyond `u64`; results are decimal
+//! strings. Every error is answered with a JSON body of the form
+//! `{"error": {"kind": "zero", "message": "zero is not a valid operand"}}`.
+
+use std::fmt;
+
+use actix_web::http::StatusCode;
+use actix_web::{error, web, HttpRequest, HttpResponse, ResponseError};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::arithmetic::{self, ArithmeticError};
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request body is not the JSON the endpoint expects.
+    InvalidRequest(String),
+    /// The request body is larger than the JSON extractor accepts.
+    PayloadTooLarge,
+    /// The request is well-formed but the operation has no result.
+    Arithmetic(ArithmeticError),
+}
+
+impl From<ArithmeticError> for ApiError {
+    fn from(e: ArithmeticError) -> Self {
+        ApiError::Arithmetic(e)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::InvalidRequest(message) => write!(f, "invalid request: {}", message),
+            ApiError::PayloadTooLarge => write!(f, "the request body is too large"),
+            ApiError::Arithmetic(e) => e.fmt(f),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: ErrorDetail<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail<'a> {
+    kind: &'a str,
+    message: String,
+}
+
+impl ApiError {
+    fn kind(&self) -> &'static str {
+        match self {
+            ApiError::InvalidRequest(_) => "invalid_request",
+            ApiError::PayloadTooLarge => "payload_too_large",
+            ApiError::Arithmetic(e) => e.kind(),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::Arithmetic(e) => status_of(e),
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            error: ErrorDetail {
+                kind: self.kind(),
+                message: self.to_string(),
+            },
+        })
+    }
+}
+
+/// The HTTP status answering an arithmetic error.
+pub fn status_of(e: &ArithmeticError) -> StatusCode {
+    match e {
+        ArithmeticError::InvalidNumber(_) => StatusCode::BAD_REQUEST,
+        ArithmeticError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        _ => StatusCode::UNPROCESSABLE_ENTITY,
+    }
+}
+
+/// A number of a request: a JSON number, or a string for larger ones.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum NumberInput {
+    Small(u64),
+    Text(String),
+}
+
+impl NumberInput {
+    fn parse(&self) -> Result<BigUint, ArithmeticError> {
+        match self {
+            NumberInput::Small(n) => Ok(BigUint::from(*n)),
+            NumberInput::Text(s) => arithmetic::parse_number(s),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NumbersRequest {
+    numbers: Vec<NumberInput>,
+}
+
+impl NumbersRequest {
+    fn parse(&self) -> Result<Vec<BigUint>, ArithmeticError> {
+        self.numbers.iter().map(NumberInput::parse).collect()
+    }
+}
+
+/// The request of the operations on two numbers.
+#[derive(Deserialize)]
+pub struct PairRequest {
+    a: NumberInput,
+    b: NumberInput,
+}
+
+#[derive(Deserialize)]
+pub struct InverseRequest {
+    value: NumberInput,
+    modulus: NumberInput,
+}
+
+#[derive(Deserialize)]
+pub struct FactorsRequest {
+    number: NumberInput,
+}
+
+// Results may not fit in a `u64`, and JSON numbers beyond 2^53 are not
+// portable anyway, so they are all given as decimal strings.
+
+#[derive(Serialize)]
+struct ResultResponse {
+    result: String,
+}
+
+#[derive(Serialize)]
+struct BezoutResponse {
+    gcd: String,
+    x: String,
+    y: String,
+}
+
+#[derive(Serialize)]
+struct EuclidStep {
+    dividend: String,
+    divisor: String,
+    quotient: String,
+    remainder: String,
+}
+
+#[derive(Serialize)]
+struct StepsResponse {
+    gcd: String,
+    steps: Vec<EuclidStep>,
+}
+
+#[derive(Serialize)]
+struct Factor {
+    prime: String,
+    exponent: u32,
+}
+
+#[derive(Serialize)]
+struct FactorsResponse {
+    factors: Vec<Factor>,
+}
+
+/// Register the API routes.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    let json_config = || {
+        web::JsonConfig::default().error_handler(|err, _: &HttpRequest| {
+            error::Error::from(match err {
+                error::JsonPayloadError::Overflow { .. }
+                | error::JsonPayloadError::OverflowKnownLength { .. } => ApiError::PayloadTooLarge,
+                err => ApiError::InvalidRequest(err.to_string()),
+            })
+        })
+    };
+    cfg.service(
+        web::resource("/api/gcd")
+            .app_data(json_config())
+            .route(web::post().to(post_gcd)),
+    )
+    .service(
+        web::resource("/api/gcd/steps")
+            .app_data(json_config())
+            .route(web::post().to(post_gcd_steps)),
+    )
+    .service(
+        web::resource("/api/lcm")
+            .app_data(json_config())
+            .route(web::post().to(post_lcm)),
+    )
+    .service(
+        web::resource("/api/bezout")
+            .app_data(json_config())
+            .route(web::post().to(post_bezout)),
+    )
+    .service(
+        web::resource("/api/inverse")
+            .app_data(json_config())
+            .route(web::post().to(post_inverse)),
+    )
+    .service(
+        web::resource("/api/factors")
+            .app_data(json_config())
+            .route(web::post().to(post_factors)),
+    );
+}
+
+async fn post_gcd(request: web::Json<NumbersRequest>) -> Result<HttpResponse, ApiError> {
+    let result = arithmetic::gcd_all(&request.parse()?)?;
+    Ok(HttpResponse::Ok().json(ResultResponse {
+        result: result.to_string(),
+    }))
+}
+
+async fn post_gcd_steps(request: web::Json<PairRequest>) -> Result<HttpResponse, ApiError> {
+    let trace = arithmetic::euclid_trace(&request.a.parse()?, &request.b.parse()?)?;
+    Ok(HttpResponse::Ok().json(StepsResponse {
+        gcd: trace.gcd.to_string(),
+        steps: trace
+            .steps
+            .into_iter()
+            .map(|step| EuclidStep {
+                dividend: step.dividend.to_string(),
+                divisor: step.divisor.to_string(),
+                quotient: step.quotient.to_string(),
+                remainder: step.remainder.to_string(),
+            })
+            .collect(),
+    }))
+}
+
+async fn post_lcm(request: web::Json<NumbersRequest>) -> Result<HttpResponse, ApiError> {
+    let result = arithmetic::lcm_all(&request.parse()?)?;
+    Ok(HttpResponse::Ok().json(ResultResponse {
+        result: result.to_string(),
+    }))
+}
+
+async fn post_bezout(request: web::Json<PairRequest>) -> Result<HttpResponse, ApiError> {
+    let bezout = arithmetic::extended_gcd(&request.a.parse()?, &request.b.parse()?)?;
+    Ok(HttpResponse::Ok().json(BezoutResponse {
+        gcd: bezout.gcd.to_string(),
+        x: bezout.x.to_string(),
+        y: bezout.y.to_string(),
+    }))
+}
+
+async fn post_inverse(request: web::Json<InverseRequest>) -> Result<HttpResponse, ApiError> {
+    let result = arithmetic::mod_inverse(&request.value.parse()?, &request.modulus.parse()?)?;
+    Ok(HttpResponse::Ok().json(ResultResponse {
+        result: result.to_string(),
+    }))
+}
+
+async fn post_factors(request: web::Json<FactorsRequest>) -> Result<HttpResponse, ApiError> {
+    // Factorisation is only practical within a machine word.
+    let number = request
+        .number
+        .parse()?
+        .to_u64()
+        .ok_or(ArithmeticError::TooLarge { max_bits: 64 })?;
+    let factors = arithmetic::factorize(number)?
+        .into_iter()
+        .map(|(prime, exponent)| Factor {
+            prime: prime.to_string(),
+            exponent,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(FactorsResponse { factors }))
+}
+
+#[cfg(test)]
+async fn call(uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    use actix_web::{test, App};
+
+    let app = test::init_service(App::new().configure(configure)).await;
+    let request = test::TestRequest::post()
+        .uri(uri)
+        .set_json(body)
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    let status = response.status();
+    (status, test::read_body_json(response).await)
+}
+
+#[actix_web::test]
+async fn test_post_gcd() {
+    use serde_json::json;
+
+    assert_eq!(
+        call("/api/gcd", json!({"numbers": [12, "18", "0x1e"]})).await,
+        (StatusCode::OK, json!({"result": "6"}))
+    );
+    assert_eq!(
+        call("/api/lcm", json!({"numbers": [u64::MAX, "2"]})).await,
+        (StatusCode::OK, json!({"result": "36893488147419103230"}))
+    );
+
+    let (status, body) = call("/api/gcd", json!({"numbers": []})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["kind"], "empty_input");
+    let (status, body) = call("/api/gcd", json!({"numbers": ["12x"]})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["kind"], "invalid_number");
+    let (status, body) = call("/api/gcd", json!({"numbers": [-1]})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["kind"], "invalid_request");
+}
+
+#[actix_web::test]
+async fn test_post_gcd_steps() {
+    use serde_json::json;
+
+    let step = |dividend: &str, divisor: &str, quotient: &str, remainder: &str| {
+        json!({
+            "dividend": dividend,
+            "divisor": divisor,
+            "quotient": quotient,
+            "remainder": remainder,
+        })
+    };
+    assert_eq!(
+        call("/api/gcd/steps", json!({"a": 36, "b": "0x30"})).await,
+        (
+            StatusCode::OK,
+            json!({
+                "gcd": "12",
+                "steps": [step("48", "36", "1", "12"), step("36", "12", "3", "0")],
+            })
+        )
+    );
+    let (status, body) = call("/api/gcd/steps", json!({"a": 0, "b": 5})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["kind"], "zero");
+}
+
+#[actix_web::test]
+async fn test_post_bezout_and_inverse() {
+    use serde_json::json;
+
+    assert_eq!(
+        call("/api/bezout", json!({"a": 240, "b": 46})).await,
+        (StatusCode::OK, json!({"gcd": "2", "x": "-9", "y": "47"}))
+    );
+    assert_eq!(
+        call("/api/inverse", json!({"value": 3, "modulus": "11"})).await,
+        (StatusCode::OK, json!({"result": "4"}))
+    );
+    let (status, body) = call("/api/inverse", json!({"value": 6, "modulus": 9})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["kind"], "not_invertible");
+}
+
+#[actix_web::test]
+async fn test_post_factors() {
+    use serde_json::json;
+
+    assert_eq!(
+        call("/api/factors", json!({"number": 360})).await,
+        (
+            StatusCode::OK,
+            json!({"factors": [
+                {"prime": "2", "exponent": 3},
+                {"prime": "3", "exponent": 2},
+                {"prime": "5", "exponent": 1},
+            ]})
+        )
+    );
+    let (status, body) = call("/api/factors", json!({"number": "0x10000000000000000"})).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["error"]["kind"], "too_large");
+}
@@ -0,0 +1,570 @@
+use std::fmt;
+
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Largest accepted operand, in bits.
+pub const MAX_BITS: u64 = 4096;
+
+/// Longest accepted textual operand: the hexadecimal prefix plus the decimal
+/// digits of `2^MAX_BITS`. Longer inputs are refused before being parsed.
+const MAX_INPUT_LENGTH: usize = 2 + 1234;
+
+/// Why an arithmetic operation has no result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArithmeticError {
+    /// The operation was given no number at all.
+    EmptyInput,
+    /// The text is neither a decimal nor a `0x`-prefixed hexadecimal number.
+    InvalidNumber(String),
+    /// An operand has more than `max_bits` bits.
+    TooLarge { max_bits: u64 },
+    /// One of the operands is zero, which the operation does not accept.
+    Zero,
+    /// `value` and `modulus` are not coprime, so `value` has no inverse.
+    NotInvertible { value: BigUint, modulus: BigUint },
+}
+
+impl ArithmeticError {
+    /// A short machine-readable name for the error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ArithmeticError::EmptyInput => "empty_input",
+            ArithmeticError::InvalidNumber(_) => "invalid_number",
+            ArithmeticError::TooLarge { .. } => "too_large",
+            ArithmeticError::Zero => "zero",
+            ArithmeticError::NotInvertible { .. } => "not_invertible",
+        }
+    }
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArithmeticError::EmptyInput => write!(f, "at least one number is required"),
+            ArithmeticError::InvalidNumber(s) => write!(
+                f,
+                "`{}` is neither a decimal nor a 0x-prefixed hexadecimal number",
+                s
+            ),
+            ArithmeticError::TooLarge { max_bits } => {
+                write!(f, "numbers are limited to {} bits", max_bits)
+            }
+            ArithmeticError::Zero => write!(f, "zero is not a valid operand"),
+            ArithmeticError::NotInvertible { value, modulus } => write!(
+                f,
+                "{} has no inverse modulo {}, as they are not coprime",
+                value, modulus
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArithmeticError {}
+
+/// Parse `s` as a non-negative integer, written in decimal or in hexadecimal
+/// with a `0x` prefix, of at most `MAX_BITS` bits.
+pub fn parse_number(s: &str) -> Result<BigUint, ArithmeticError> {
+    let s = s.trim();
+    if s.len() > MAX_INPUT_LENGTH {
+        return Err(ArithmeticError::TooLarge { max_bits: MAX_BITS });
+    }
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => (digits, 16),
+        None => (s, 10),
+    };
+    // `BigUint::parse_bytes` would also accept underscores.
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(ArithmeticError::InvalidNumber(s.to_string()));
+    }
+    let n = BigUint::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| ArithmeticError::InvalidNumber(s.to_string()))?;
+    if n.bits() > MAX_BITS {
+        return Err(ArithmeticError::TooLarge { max_bits: MAX_BITS });
+    }
+    Ok(n)
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(parse_number("42"), Ok(BigUint::from(42_u32)));
+    assert_eq!(parse_number(" 0x2A "), Ok(BigUint::from(42_u32)));
+    assert_eq!(parse_number("0Xff"), Ok(BigUint::from(255_u32)));
+    assert_eq!(
+        parse_number("18446744073709551616"),
+        Ok(BigUint::from(u64::MAX) + 1_u32)
+    );
+    for invalid in ["", "0x", "-1", "1_000", "12a", "0xfg"] {
+        assert_eq!(
+            parse_number(invalid),
+            Err(ArithmeticError::InvalidNumber(invalid.to_string()))
+        );
+    }
+    let too_large = ArithmeticError::TooLarge { max_bits: MAX_BITS };
+    let limit = BigUint::one() << MAX_BITS;
+    assert_eq!(
+        parse_number(&(&limit - 1_u32).to_string()).map(|n| n.bits()),
+        Ok(MAX_BITS)
+    );
+    assert_eq!(parse_number(&limit.to_string()), Err(too_large.clone()));
+    assert_eq!(parse_number(&"9".repeat(5000)), Err(too_large));
+}
+
+/// Compute the greatest common divisor of `n` and `m` with Euclid's
+/// algorithm. Both must be non-zero.
+pub fn gcd(n: u64, m: u64) -> Result<u64, ArithmeticError> {
+    euclid(n, m, |_, _, _, _| ())
+}
+
+/// Euclid's algorithm on `a` and `b`, both non-zero, calling `on_step` with
+/// the dividend, divisor, quotient and remainder of each division.
+fn euclid<T: Integer>(
+    a: T,
+    b: T,
+    mut on_step: impl FnMut(&T, &T, &T, &T),
+) -> Result<T, ArithmeticError> {
+    if a.is_zero() || b.is_zero() {
+        return Err(ArithmeticError::Zero);
+    }
+    let (mut dividend, mut divisor) = if a >= b { (a, b) } else { (b, a) };
+    loop {
+        let (quotient, remainder) = dividend.div_rem(&divisor);
+        on_step(&dividend, &divisor, &quotient, &remainder);
+        if remainder.is_zero() {
+            return Ok(divisor);
+        }
+        dividend = std::mem::replace(&mut divisor, remainder);
+    }
+}
+
+#[test]
+fn test_gcd() {
+    assert_eq!(gcd(14, 15), Ok(1));
+    assert_eq!(gcd(2 * 3 * 5 * 11 * 17, 3 * 7 * 11 * 13 * 19), Ok(3 * 11));
+    assert_eq!(gcd(0, 15), Err(ArithmeticError::Zero));
+    assert_eq!(gcd(14, 0), Err(ArithmeticError::Zero));
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn gcd_matches_num_integer(n in 1..=u64::MAX, m in 1..=u64::MAX) {
+        proptest::prop_assert_eq!(gcd(n, m), Ok(num_integer::Integer::gcd(&n, &m)));
+    }
+
+    #[test]
+    fn gcd_divides_both(n in 1..=u64::MAX, m in 1..=u64::MAX) {
+        let d = gcd(n, m).unwrap();
+        proptest::prop_assert_eq!((n % d, m % d), (0, 0));
+        proptest::prop_assert_eq!(gcd(n / d, m / d), Ok(1));
+    }
+
+    #[test]
+    fn euclid_trace_matches_gcd(n in 1..=u64::MAX, m in 1..=u64::MAX) {
+        let trace = euclid_trace(&n.into(), &m.into()).unwrap();
+        proptest::prop_assert_eq!(trace.gcd, BigUint::from(gcd(n, m).unwrap()));
+        for step in &trace.steps {
+            proptest::prop_assert_eq!(
+                &step.dividend,
+                &(&step.quotient * &step.divisor + &step.remainder)
+            );
+        }
+    }
+
+    #[test]
+    fn binary_gcd_matches_num_integer(
+        a in proptest::collection::vec(proptest::num::u32::ANY, 1..8),
+        b in proptest::collection::vec(proptest::num::u32::ANY, 1..8),
+    ) {
+        let (a, b) = (BigUint::new(a) + 1_u32, BigUint::new(b) + 1_u32);
+        proptest::prop_assert_eq!(binary_gcd(&a, &b), num_integer::Integer::gcd(&a, &b));
+    }
+}
+
+/// Compute the greatest common divisor of `a` and `b`, both non-zero, with
+/// Stein's binary algorithm.
+///
+/// Unlike Euclid's algorithm, it only needs shifts and subtractions, which
+/// take linear time on big integers where a division does not.
+pub fn binary_gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    let (a_zeros, b_zeros) = match (a.trailing_zeros(), b.trailing_zeros()) {
+        (Some(a_zeros), Some(b_zeros)) => (a_zeros, b_zeros),
+        _ => panic!("binary_gcd of zero"),
+    };
+    // Both stay odd from here on: the common power of two is put back at the end.
+    let mut a = a >> a_zeros;
+    let mut b = b >> b_zeros;
+    loop {
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b -= &a;
+        match b.trailing_zeros() {
+            Some(zeros) => b >>= zeros,
+            None => return a << a_zeros.min(b_zeros),
+        }
+    }
+}
+
+#[test]
+fn test_binary_gcd() {
+    let big = |n: u64| BigUint::from(n);
+    assert_eq!(binary_gcd(&big(14), &big(15)), big(1));
+    assert_eq!(binary_gcd(&big(48), &big(180)), big(12));
+    assert_eq!(binary_gcd(&big(1 << 40), &big(1 << 10)), big(1 << 10));
+
+    let a = BigUint::from(3_u32).pow(200) * BigUint::from(2_u32).pow(90) * 7_u32;
+    let b = BigUint::from(3_u32).pow(150) * BigUint::from(2_u32).pow(120) * 11_u32;
+    let expected = BigUint::from(3_u32).pow(150) * BigUint::from(2_u32).pow(90);
+    assert_eq!(binary_gcd(&a, &b), expected);
+    assert_eq!(
+        binary_gcd(&a, &b),
+        num_integer::Integer::gcd(&a, &b),
+        "agrees with num-integer"
+    );
+}
+
+/// One division of Euclid's algorithm: `dividend = quotient * divisor + remainder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EuclidStep {
+    pub dividend: BigUint,
+    pub divisor: BigUint,
+    pub quotient: BigUint,
+    pub remainder: BigUint,
+}
+
+/// The greatest common divisor of two numbers, with the divisions of
+/// Euclid's algorithm that led to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EuclidTrace {
+    pub gcd: BigUint,
+    pub steps: Vec<EuclidStep>,
+}
+
+/// Compute the greatest common divisor of `a` and `b`, both non-zero, with
+/// `gcd`'s algorithm, recording each division.
+///
+/// This is for teaching: `binary_gcd` is faster.
+pub fn euclid_trace(a: &BigUint, b: &BigUint) -> Result<EuclidTrace, ArithmeticError> {
+    let mut steps = vec![];
+    let gcd = euclid(
+        a.clone(),
+        b.clone(),
+        |dividend, divisor, quotient, remainder| {
+            steps.push(EuclidStep {
+                dividend: dividend.clone(),
+                divisor: divisor.clone(),
+                quotient: quotient.clone(),
+                remainder: remainder.clone(),
+            })
+        },
+    )?;
+    Ok(EuclidTrace { gcd, steps })
+}
+
+#[test]
+fn test_euclid_trace() {
+    let step = |dividend: u32, divisor: u32, quotient: u32, remainder: u32| EuclidStep {
+        dividend: dividend.into(),
+        divisor: divisor.into(),
+        quotient: quotient.into(),
+        remainder: remainder.into(),
+    };
+    assert_eq!(
+        euclid_trace(&46_u32.into(), &240_u32.into()),
+        Ok(EuclidTrace {
+            gcd: 2_u32.into(),
+            steps: vec![
+                step(240, 46, 5, 10),
+                step(46, 10, 4, 6),
+                step(10, 6, 1, 4),
+                step(6, 4, 1, 2),
+                step(4, 2, 2, 0),
+            ],
+        })
+    );
+    assert_eq!(
+        euclid_trace(&7_u32.into(), &7_u32.into()).map(|trace| trace.steps),
+        Ok(vec![step(7, 7, 1, 0)])
+    );
+    assert_eq!(
+        euclid_trace(&0_u32.into(), &7_u32.into()),
+        Err(ArithmeticError::Zero)
+    );
+}
+
+/// The greatest common divisor of all of `numbers`.
+pub fn gcd_all(numbers: &[BigUint]) -> Result<BigUint, ArithmeticError> {
+    let (first, rest) = numbers.split_first().ok_or(ArithmeticError::EmptyInput)?;
+    if numbers.iter().any(Zero::is_zero) {
+        return Err(ArithmeticError::Zero);
+    }
+    Ok(rest.iter().fold(first.clone(), |d, n| binary_gcd(&d, n)))
+}
+
+#[cfg(test)]
+fn bigs(numbers: &[u64]) -> Vec<BigUint> {
+    numbers.iter().map(|n| BigUint::from(*n)).collect()
+}
+
+#[test]
+fn test_gcd_all() {
+    assert_eq!(gcd_all(&bigs(&[12])), Ok(BigUint::from(12_u32)));
+    assert_eq!(gcd_all(&bigs(&[12, 18, 30])), Ok(BigUint::from(6_u32)));
+    assert_eq!(gcd_all(&[]), Err(ArithmeticError::EmptyInput));
+    assert_eq!(gcd_all(&bigs(&[12, 0])), Err(ArithmeticError::Zero));
+}
+
+/// The least common multiple of all of `numbers`.
+pub fn lcm_all(numbers: &[BigUint]) -> Result<BigUint, ArithmeticError> {
+    let (first, rest) = numbers.split_first().ok_or(ArithmeticError::EmptyInput)?;
+    if numbers.iter().any(Zero::is_zero) {
+        return Err(ArithmeticError::Zero);
+    }
+    Ok(rest
+        .iter()
+        .fold(first.clone(), |l, n| &l / binary_gcd(&l, n) * n))
+}
+
+#[test]
+fn test_lcm_all() {
+    assert_eq!(lcm_all(&bigs(&[4, 6])), Ok(BigUint::from(12_u32)));
+    assert_eq!(lcm_all(&bigs(&[2, 3, 4, 5])), Ok(BigUint::from(60_u32)));
+    assert_eq!(lcm_all(&bigs(&[7])), Ok(BigUint::from(7_u32)));
+    assert_eq!(lcm_all(&[]), Err(ArithmeticError::EmptyInput));
+    assert_eq!(lcm_all(&bigs(&[3, 0])), Err(ArithmeticError::Zero));
+    assert_eq!(
+        lcm_all(&bigs(&[u64::MAX, u64::MAX - 1])),
+        Ok(BigUint::from(u64::MAX) * (u64::MAX - 1))
+    );
+}
+
+/// The greatest common divisor `gcd` of `a` and `b`, with Bézout
+/// coefficients `x` and `y` such that `a * x + b * y == gcd`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bezout {
+    pub gcd: BigUint,
+    pub x: BigInt,
+    pub y: BigInt,
+}
+
+/// Run the extended Euclidean algorithm on `a` and `b`, both non-zero.
+pub fn extended_gcd(a: &BigUint, b: &BigUint) -> Result<Bezout, ArithmeticError> {
+    if a.is_zero() || b.is_zero() {
+        return Err(ArithmeticError::Zero);
+    }
+    // Invariants: a * old_x + b * old_y == old_r and a * x + b * y == r.
+    let (mut old_r, mut r) = (BigInt::from(a.clone()), BigInt::from(b.clone()));
+    let (mut old_x, mut x) = (BigInt::one(), BigInt::zero());
+    let (mut old_y, mut y) = (BigInt::zero(), BigInt::one());
+    while !r.is_zero() {
+        let quotient = &old_r / &r;
+        let next_r = &old_r - &quotient * &r;
+        let next_x = &old_x - &quotient * &x;
+        let next_y = &old_y - &quotient * &y;
+        old_r = std::mem::replace(&mut r, next_r);
+        old_x = std::mem::replace(&mut x, next_x);
+        old_y = std::mem::replace(&mut y, next_y);
+    }
+    Ok(Bezout {
+        gcd: old_r.magnitude().clone(),
+        x: old_x,
+        y: old_y,
+    })
+}
+
+#[test]
+fn test_extended_gcd() {
+    let check = |a: &BigUint, b: &BigUint, gcd: &BigUint| {
+        let bezout = extended_gcd(a, b).unwrap();
+        assert_eq!(&bezout.gcd, gcd);
+        assert_eq!(
+            BigInt::from(a.clone()) * &bezout.x + BigInt::from(b.clone()) * &bezout.y,
+            BigInt::from(gcd.clone())
+        );
+    };
+    check(&240_u32.into(), &46_u32.into(), &2_u32.into());
+    check(&u64::MAX.into(), &(u64::MAX - 1).into(), &1_u32.into());
+    let a = BigUint::from(3_u32).pow(300) * 4_u32;
+    let b = BigUint::from(2_u32).pow(500) * 9_u32;
+    check(&a, &b, &36_u32.into());
+
+    assert_eq!(
+        extended_gcd(&0_u32.into(), &5_u32.into()),
+        Err(ArithmeticError::Zero)
+    );
+}
+
+/// The inverse of `value` modulo `modulus`, in `0..modulus`.
+pub fn mod_inverse(value: &BigUint, modulus: &BigUint) -> Result<BigUint, ArithmeticError> {
+    if modulus.is_zero() {
+        return Err(ArithmeticError::Zero);
+    }
+    if modulus.is_one() {
+        return Ok(BigUint::zero());
+    }
+    let not_invertible = || ArithmeticError::NotInvertible {
+        value: value.clone(),
+        modulus: modulus.clone(),
+    };
+    let reduced = value % modulus;
+    if reduced.is_zero() {
+        return Err(not_invertible());
+    }
+    let bezout = extended_gcd(&reduced, modulus)?;
+    if !bezout.gcd.is_one() {
+        return Err(not_invertible());
+    }
+    let modulus = BigInt::from(modulus.clone());
+    let inverse = ((bezout.x % &modulus) + &modulus) % &modulus;
+    Ok(inverse.magnitude().clone())
+}
+
+#[test]
+fn test_mod_inverse() {
+    let inverse = |value: u64, modulus: u64| {
+        mod_inverse(&value.into(), &modulus.into()).map(|n| n.to_string())
+    };
+    assert_eq!(inverse(3, 11), Ok("4".to_string()));
+    assert_eq!(inverse(10, 17), Ok("12".to_string()));
+    assert_eq!(inverse(25, 7), Ok("2".to_string()));
+    assert_eq!(inverse(5, 1), Ok("0".to_string()));
+    assert_eq!(
+        inverse(6, 9),
+        Err(ArithmeticError::NotInvertible {
+            value: 6_u32.into(),
+            modulus: 9_u32.into()
+        })
+    );
+    assert_eq!(inverse(3, 0), Err(ArithmeticError::Zero));
+
+    // 2^127 - 1 is prime, so every smaller positive number is invertible.
+    let prime = (BigUint::one() << 127) - 1_u32;
+    let value = BigUint::from(123_456_789_u64).pow(3);
+    let result = mod_inverse(&value, &prime).unwrap();
+    assert!((value * result % &prime).is_one());
+}
+
+/// The prime factorisation of `n`, as increasing `(prime, exponent)` pairs.
+///
+/// Small factors are found by trial division, the remaining ones with
+/// Pollard's rho algorithm, using a deterministic Miller-Rabin test to know
+/// when a factor is prime.
+pub fn factorize(n: u64) -> Result<Vec<(u64, u32)>, ArithmeticError> {
+    if n == 0 {
+        return Err(ArithmeticError::Zero);
+    }
+    let mut primes = vec![];
+    let mut n = n;
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    let mut pending = vec![n];
+    while let Some(n) = pending.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime(n) {
+            primes.push(n);
+            continue;
+        }
+        let d = pollard_rho(n);
+        pending.push(d);
+        pending.push(n / d);
+    }
+    primes.sort_unstable();
+
+    let mut factors: Vec<(u64, u32)> = vec![];
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Ok(factors)
+}
+
+#[test]
+fn test_factorize() {
+    assert_eq!(factorize(1), Ok(vec![]));
+    assert_eq!(factorize(360), Ok(vec![(2, 3), (3, 2), (5, 1)]));
+    assert_eq!(factorize(97), Ok(vec![(97, 1)]));
+    assert_eq!(
+        factorize(600_851_475_143),
+        Ok(vec![(71, 1), (839, 1), (1471, 1), (6857, 1)])
+    );
+    // Product of two primes just below 2^32.
+    assert_eq!(
+        factorize(4_294_967_291 * 4_294_967_279),
+        Ok(vec![(4_294_967_279, 1), (4_294_967_291, 1)])
+    );
+    assert_eq!(factorize(u64::MAX - 58), Ok(vec![(u64::MAX - 58, 1)]));
+    assert_eq!(factorize(0), Err(ArithmeticError::Zero));
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(m)) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Miller-Rabin primality test, deterministic for every `u64` with these bases.
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        (1..s).any(|_| {
+            x = mul_mod(x, x, n);
+            x == n - 1
+        })
+    })
+}
+
+/// Find a non-trivial divisor of the odd composite `n`.
+fn pollard_rho(n: u64) -> u64 {
+    let mut c: u64 = 1;
+    loop {
+        let step = |x: u64| ((u128::from(mul_mod(x, x, n)) + u128::from(c)) % u128::from(n)) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = step(x);
+            y = step(step(y));
+            d = gcd(x.abs_diff(y).max(1), n).expect("both operands are non-zero");
+            if x == y {
+                break;
+            }
+        }
+        if d != 1 && d != n {
+            return d;
+        }
+        c += 1;
+    }
+}
@@ -1,64 +1,268 @@
+mod api;
+mod arithmetic;
+
 use actix_web::{web, App, HttpResponse, HttpServer};
+use num_bigint::BigUint;
 use serde::Deserialize;
 
+use arithmetic::{ArithmeticError, EuclidTrace};
+
+/// The two numbers of the form, in decimal or `0x`-prefixed hexadecimal.
 #[derive(Deserialize)]
 struct GcdParameters {
-    n: u64,
-    m: u64,
+    n: String,
+    m: String,
+    /// Present when the steps of Euclid's algorithm are to be shown.
+    #[serde(default)]
+    steps: Option<String>,
+}
+
+/// Where and how the server listens, from the environment.
+#[derive(Debug, PartialEq)]
+struct ServerConfig {
+    /// `GCD_ADDRESS`, `127.0.0.1` by default.
+    address: String,
+    /// `GCD_PORT`, 3000 by default.
+    port: u16,
+    /// `GCD_SHUTDOWN_TIMEOUT`: how many seconds in-flight requests are given
+    /// to complete once the server is asked to stop, 30 by default.
+    shutdown_timeout: u64,
+}
+
+impl ServerConfig {
+    fn from_env() -> Result<ServerConfig, String> {
+        ServerConfig::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<ServerConfig, String> {
+        let parse = |name: &str, default: u64, max: u64| match lookup(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|n| *n <= max)
+                .ok_or_else(|| format!("{} is not a valid {}", value, name)),
+        };
+        Ok(ServerConfig {
+            address: lookup("GCD_ADDRESS").unwrap_or_else(|| "127.0.0.1".to_string()),
+            port: parse("GCD_PORT", 3000, u16::MAX.into())? as u16,
+            shutdown_timeout: parse("GCD_SHUTDOWN_TIMEOUT", 30, u64::MAX)?,
+        })
+    }
+}
+
+#[test]
+fn test_server_config() {
+    let lookup = |vars: &'static [(&str, &str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+    assert_eq!(
+        ServerConfig::from_lookup(lookup(&[])),
+        Ok(ServerConfig {
+            address: "127.0.0.1".to_string(),
+            port: 3000,
+            shutdown_timeout: 30,
+        })
+    );
+    assert_eq!(
+        ServerConfig::from_lookup(lookup(&[
+            ("GCD_ADDRESS", "0.0.0.0"),
+            ("GCD_PORT", "8080"),
+            ("GCD_SHUTDOWN_TIMEOUT", "5"),
+        ])),
+        Ok(ServerConfig {
+            address: "0.0.0.0".to_string(),
+            port: 8080,
+            shutdown_timeout: 5,
+        })
+    );
+    assert_eq!(
+        ServerConfig::from_lookup(lookup(&[("GCD_PORT", "65536")])),
+        Err("65536 is not a valid GCD_PORT".to_string())
+    );
 }
 
-fn main() {
-    let server = HttpServer::new(|| {
-        App::new()
-            .route("/", web::get().to(get_index))
-            .route("/gcd", web::post().to(post_gcd))
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let config = ServerConfig::from_env().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
     });
-    println!("Serving on http://localhost:3000...");
-    server
-        .bind("127.0.0.1:3000")
-        .expect("error binding server to address")
-        .run()
-        .expect("error running server");
+    // The server stops gracefully on SIGINT, SIGTERM and SIGQUIT: it stops
+    // accepting connections and waits up to `shutdown_timeout` seconds for
+    // the requests in progress.
+    let server = HttpServer::new(|| App::new().configure(configure))
+        .shutdown_timeout(config.shutdown_timeout)
+        .bind((config.address.as_str(), config.port))?;
+    println!("Serving on http://{}:{}...", config.address, config.port);
+    server.run().await?;
+    println!("Server stopped.");
+    Ok(())
+}
+
+/// Register every route of the calculator.
+fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/", web::get().to(get_index))
+        .route("/gcd", web::post().to(post_gcd))
+        .configure(api::configure);
 }
 
-fn get_index() -> HttpResponse {
+async fn get_index() -> HttpResponse {
     HttpResponse::Ok().content_type("text/html").body(
         r#"
 <title>GCD Calculator</title>
 <form action="/gcd" method="post">
 <input type="text" name="n"/>
 <input type="text" name="m"/>
+<label><input type="checkbox" name="steps"/> Show the steps</label>
 <button type="submit">Compute GCD</button>
 </form>
 "#,
     )
 }
 
-fn gcd(mut n: u64, mut m: u64) -> u64 {
-    assert!(n != 0 && m != 0);
-    while m != 0 {
-        if m < n {
-            let t = m;
-            m = n;
-            n = t;
+async fn post_gcd(form: web::Form<GcdParameters>) -> HttpResponse {
+    let (n, m, d, trace) = match gcd_of(&form) {
+        Ok(result) => result,
+        Err(e) => {
+            return HttpResponse::build(api::status_of(&e))
+                .content_type("text/html")
+                .body(format!(
+                    "Cannot compute the GCD: {}.\n",
+                    escape_html(&e.to_string())
+                ))
         }
-        m = m % n;
+    };
+    let mut response = format!(
+        "The greatest common divisor of the numbers {} and {} \
+  is <b>{}</b>\n",
+        n, m, d
+    );
+    if let Some(trace) = trace {
+        response.push_str(&steps_table(&trace));
     }
-    n
+    HttpResponse::Ok().content_type("text/html").body(response)
 }
 
-fn post_gcd(form: web::Form<GcdParameters>) -> HttpResponse {
-    if form.n == 0 || form.m == 0 {
-        return HttpResponse::BadRequest()
-            .content_type("text/html")
-            .body("Computing the GCD with zero is boring.");
+/// Render the divisions of Euclid's algorithm as an HTML table.
+fn steps_table(trace: &EuclidTrace) -> String {
+    let mut table = String::from(
+        "<table>\n<tr><th>Step</th><th>Dividend</th><th>Divisor</th>\
+         <th>Quotient</th><th>Remainder</th></tr>\n",
+    );
+    for (i, step) in trace.steps.iter().enumerate() {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            i + 1,
+            step.dividend,
+            step.divisor,
+            step.quotient,
+            step.remainder
+        ));
     }
-    let response = format!(
-        "The greatest common divisor of the numbers {} and {} \
-  is <b>{}</b>\n",
-        form.n,
-        form.m,
-        gcd(form.n, form.m)
+    table.push_str("</table>\n");
+    table
+}
+
+#[test]
+fn test_steps_table() {
+    let trace = arithmetic::euclid_trace(&48_u32.into(), &36_u32.into()).unwrap();
+    assert_eq!(
+        steps_table(&trace),
+        "<table>\n<tr><th>Step</th><th>Dividend</th><th>Divisor</th>\
+         <th>Quotient</th><th>Remainder</th></tr>\n\
+         <tr><td>1</td><td>48</td><td>36</td><td>1</td><td>12</td></tr>\n\
+         <tr><td>2</td><td>36</td><td>12</td><td>3</td><td>0</td></tr>\n\
+         </table>\n"
+    );
+}
+
+#[actix_web::test]
+async fn test_get_index() {
+    use actix_web::test;
+
+    let app = test::init_service(App::new().configure(configure)).await;
+    let response = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    assert!(response.status().is_success());
+    let body = test::read_body(response).await;
+    assert!(std::str::from_utf8(&body)
+        .unwrap()
+        .contains(r#"<form action="/gcd""#));
+}
+
+#[actix_web::test]
+async fn test_post_gcd() {
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+
+    let app = test::init_service(App::new().configure(configure)).await;
+    let post = |n: &str, m: &str| {
+        test::TestRequest::post()
+            .uri("/gcd")
+            .set_form([("n", n), ("m", m)])
+            .to_request()
+    };
+
+    let response = test::call_service(&app, post("0x30", "36")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = test::read_body(response).await;
+    assert!(std::str::from_utf8(&body)
+        .unwrap()
+        .contains("numbers 48 and 36 is <b>12</b>"));
+
+    let request = test::TestRequest::post()
+        .uri("/gcd")
+        .set_form([("n", "48"), ("m", "36"), ("steps", "on")])
+        .to_request();
+    let body = test::read_body(test::call_service(&app, request).await).await;
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.contains("is <b>12</b>"));
+    assert!(body.contains("<td>2</td><td>36</td><td>12</td><td>3</td><td>0</td>"));
+
+    let response = test::call_service(&app, post("0", "36")).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let response = test::call_service(&app, post("<b>", "36")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = test::read_body(response).await;
+    assert!(std::str::from_utf8(&body).unwrap().contains("&lt;b&gt;"));
+    let response = test::call_service(&app, post(&"9".repeat(2000), "36")).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// The numbers of the form, their greatest common divisor, and the steps of
+/// Euclid's algorithm if asked for.
+type GcdResult = (BigUint, BigUint, BigUint, Option<EuclidTrace>);
+
+/// Parse the numbers of the form, and compute their greatest common divisor.
+///
+/// The steps of Euclid's algorithm are only traced when the form asks for them.
+fn gcd_of(form: &GcdParameters) -> Result<GcdResult, ArithmeticError> {
+    let n = arithmetic::parse_number(&form.n)?;
+    let m = arithmetic::parse_number(&form.m)?;
+    let d = arithmetic::gcd_all(&[n.clone(), m.clone()])?;
+    let trace = match form.steps {
+        Some(_) => Some(arithmetic::euclid_trace(&n, &m)?),
+        None => None,
+    };
+    Ok((n, m, d, trace))
+}
+
+/// Escape `text`, which may quote the user's input, for an HTML page.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html(r#"<b a="1">&</b>"#),
+        "&lt;b a=&quot;1&quot;&gt;&amp;&lt;/b&gt;"
     );
-    HttpResponse::Ok().content_type("text/html").body(response)
 }
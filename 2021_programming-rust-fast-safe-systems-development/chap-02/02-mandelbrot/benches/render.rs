@@ -0,0 +1,65 @@
+//! Compare the serial and parallel renderers on the book's example image,
+//! and the escape-time kernels on a view full of small bulbs and filaments.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use mandelbrot::kernel::{escape_time_checked, escape_times};
+use mandelbrot::parallel::Renderer;
+use mandelbrot::{escape_time, pixel_to_point};
+use num::Complex;
+
+fn renderers(c: &mut Criterion) {
+    let bounds = (400, 300);
+    let upper_left = Complex {
+        re: -1.20,
+        im: 0.35,
+    };
+    let lower_right = Complex { re: -1.0, im: 0.20 };
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut pixels = vec![0; bounds.0 * bounds.1];
+
+    let mut group = c.benchmark_group("render");
+    for renderer in [Renderer::Serial, Renderer::Bands, Renderer::Rayon] {
+        group.bench_function(BenchmarkId::new(format!("{:?}", renderer), threads), |b| {
+            b.iter(|| renderer.render(&mut pixels, bounds, upper_left, lower_right, threads))
+        });
+    }
+    group.finish();
+}
+
+fn kernels(c: &mut Criterion) {
+    let bounds = (200, 150);
+    let upper_left = Complex {
+        re: -1.20,
+        im: 0.35,
+    };
+    let lower_right = Complex { re: -1.0, im: 0.20 };
+    let limit = 1000;
+    let points: Vec<_> = (0..bounds.1)
+        .flat_map(|row| (0..bounds.0).map(move |column| (column, row)))
+        .map(|pixel| pixel_to_point(bounds, pixel, upper_left, lower_right))
+        .collect();
+
+    let mut group = c.benchmark_group("kernel");
+    let mut times = vec![None; points.len()];
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            for (time, c) in times.iter_mut().zip(&points) {
+                *time = escape_time(*c, limit);
+            }
+        })
+    });
+    group.bench_function("checked", |b| {
+        b.iter(|| {
+            for (time, c) in times.iter_mut().zip(&points) {
+                *time = escape_time_checked(*c, limit);
+            }
+        })
+    });
+    group.bench_function("simd", |b| {
+        b.iter(|| escape_times(&points, limit, &mut times))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, renderers, kernels);
+criterion_main!(benches);
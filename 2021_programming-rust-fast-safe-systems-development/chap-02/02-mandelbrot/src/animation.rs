@@ -0,0 +1,207 @@
+//! Zoom animations: a sequence of views closing in on a target point.
+//!
+//! Frames are rendered like still images; this module only says which part
+//! of the plane each frame shows, and writes the frames out as numbered PNG
+//! files or as an animated GIF.
+
+use std::fs::File;
+use std::io;
+use std::str::FromStr;
+
+use num::Complex;
+
+/// How the zoom speeds up and slows down over the animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// The progress of the zoom at time `t`, both from 0 to 1.
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Easing::Linear),
+            "ease-in" => Ok(Easing::EaseIn),
+            "ease-out" => Ok(Easing::EaseOut),
+            "ease-in-out" => Ok(Easing::EaseInOut),
+            _ => Err(format!(
+                "unknown easing `{}`: expected linear, ease-in, ease-out or ease-in-out",
+                s
+            )),
+        }
+    }
+}
+
+/// A zoom on `target`, from a view `start_width` wide to one `end_width`
+/// wide, over `frames` frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zoom {
+    pub target: Complex<f64>,
+    pub start_width: f64,
+    pub end_width: f64,
+    pub frames: usize,
+    pub easing: Easing,
+}
+
+impl Zoom {
+    /// The width of the view of frame `frame`.
+    ///
+    /// Widths are interpolated geometrically, so that with linear easing
+    /// every frame magnifies the previous one by the same factor.
+    pub fn width(&self, frame: usize) -> f64 {
+        let t = if self.frames > 1 {
+            frame as f64 / (self.frames - 1) as f64
+        } else {
+            0.0
+        };
+        let ratio = self.end_width / self.start_width;
+        self.start_width * ratio.powf(self.easing.apply(t))
+    }
+
+    /// The upper-left and lower-right corners of frame `frame`, for images
+    /// of size `bounds`.
+    pub fn corners(&self, frame: usize, bounds: (usize, usize)) -> (Complex<f64>, Complex<f64>) {
+        let width = self.width(frame);
+        let height = width * bounds.1 as f64 / bounds.0 as f64;
+        let half = Complex {
+            re: width / 2.0,
+            im: -height / 2.0,
+        };
+        (self.target - half, self.target + half)
+    }
+}
+
+#[test]
+fn test_zoom() {
+    let zoom = Zoom {
+        target: Complex { re: -0.75, im: 0.1 },
+        start_width: 4.0,
+        end_width: 0.04,
+        frames: 3,
+        easing: Easing::Linear,
+    };
+    assert_eq!(zoom.width(0), 4.0);
+    assert!((zoom.width(1) - 0.4).abs() < 1e-12);
+    assert!((zoom.width(2) - 0.04).abs() < 1e-12);
+    assert_eq!(
+        zoom.corners(0, (200, 100)),
+        (
+            Complex { re: -2.75, im: 1.1 },
+            Complex { re: 1.25, im: -0.9 }
+        )
+    );
+
+    let eased = Zoom {
+        easing: Easing::EaseInOut,
+        frames: 5,
+        ..zoom
+    };
+    // Easing changes the pace, not where the zoom starts, ends, or is half way.
+    assert_eq!(eased.width(0), 4.0);
+    assert!((eased.width(2) - 0.4).abs() < 1e-12);
+    assert!((eased.width(4) - 0.04).abs() < 1e-12);
+    assert!(eased.width(1) > 4.0 * 0.1_f64.powf(0.5));
+
+    for easing in [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ] {
+        assert_eq!((easing.apply(0.0), easing.apply(1.0)), (0.0, 1.0));
+    }
+    assert_eq!("ease-out".parse(), Ok(Easing::EaseOut));
+    assert!("bounce".parse::<Easing>().is_err());
+}
+
+/// The file name of frame `frame` out of `frames`, numbered before the
+/// extension of `path`: `zoom.png` gives `zoom-0007.png`.
+pub fn frame_filename(path: &str, frame: usize, frames: usize) -> String {
+    let digits = frames.saturating_sub(1).to_string().len().max(4);
+    match path.rfind('.') {
+        Some(dot) if !path[dot..].contains('/') => format!(
+            "{}-{:0width$}{}",
+            &path[..dot],
+            frame,
+            &path[dot..],
+            width = digits
+        ),
+        _ => format!("{}-{:0width$}", path, frame, width = digits),
+    }
+}
+
+#[test]
+fn test_frame_filename() {
+    assert_eq!(frame_filename("zoom.png", 7, 100), "zoom-0007.png");
+    assert_eq!(
+        frame_filename("out/zoom.png", 12345, 20000),
+        "out/zoom-12345.png"
+    );
+    assert_eq!(frame_filename("out.d/zoom", 1, 2), "out.d/zoom-0001");
+}
+
+/// Writes RGB frames into an animated GIF, looping forever.
+pub struct GifWriter {
+    encoder: gif::Encoder<File>,
+    bounds: (u16, u16),
+    /// Time each frame is shown, in hundredths of a second.
+    delay: u16,
+}
+
+fn gif_error(e: gif::EncodingError) -> io::Error {
+    match e {
+        gif::EncodingError::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+impl GifWriter {
+    /// Create the file `filename` for frames of size `bounds`, shown at
+    /// `fps` frames per second.
+    pub fn create(filename: &str, bounds: (usize, usize), fps: u32) -> io::Result<GifWriter> {
+        let too_large = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GIF images are at most 65535 pixels wide",
+            )
+        };
+        let bounds = (
+            u16::try_from(bounds.0).map_err(|_| too_large())?,
+            u16::try_from(bounds.1).map_err(|_| too_large())?,
+        );
+        let mut encoder = gif::Encoder::new(File::create(filename)?, bounds.0, bounds.1, &[])
+            .map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+        Ok(GifWriter {
+            encoder,
+            bounds,
+            delay: (100 / fps.max(1)).max(1) as u16,
+        })
+    }
+
+    /// Append a frame of RGB pixels, as `colour::colourize` returns them.
+    pub fn write_frame(&mut self, pixels: &[u8]) -> io::Result<()> {
+        // Each frame gets its own palette of 256 colours, chosen by NeuQuant.
+        let mut frame = gif::Frame::from_rgb_speed(self.bounds.0, self.bounds.1, pixels, 10);
+        frame.delay = self.delay;
+        self.encoder.write_frame(&frame).map_err(gif_error)
+    }
+}
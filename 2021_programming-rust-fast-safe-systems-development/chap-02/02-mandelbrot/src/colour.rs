@@ -0,0 +1,513 @@
+//! Colouring escape times with palettes.
+//!
+//! Rendering in colour takes two passes: `escape_values` computes how fast
+//! each point escapes, possibly as a fractional, smooth count, then
+//! `colourize` maps those values to RGB through a `Palette`, after histogram
+//! equalisation if asked.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use num::Complex;
+
+use crate::fractal::Fractal;
+use crate::pixel_to_point;
+
+/// The colour of a pixel, as red, green and blue bytes.
+pub type Rgb = [u8; 3];
+
+/// A gradient between colours, for values from 0 to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Increasing positions from 0 to 1, with the colour at each.
+    stops: Vec<(f64, Rgb)>,
+}
+
+/// The built-in palettes, by name.
+pub const PALETTES: [&str; 4] = ["grayscale", "fire", "ocean", "rainbow"];
+
+impl Palette {
+    /// A palette going evenly through `colours`.
+    pub fn evenly_spaced(colours: &[Rgb]) -> Palette {
+        assert!(!colours.is_empty());
+        let last = (colours.len() - 1).max(1) as f64;
+        Palette {
+            stops: colours
+                .iter()
+                .enumerate()
+                .map(|(i, colour)| (i as f64 / last, *colour))
+                .collect(),
+        }
+    }
+
+    /// One of the built-in `PALETTES`.
+    pub fn named(name: &str) -> Option<Palette> {
+        let colours: &[Rgb] = match name {
+            // From white for points escaping at once, to black for the
+            // slowest: the colouring of the original grayscale renderer.
+            "grayscale" => &[[255, 255, 255], [0, 0, 0]],
+            "fire" => &[
+                [0, 0, 0],
+                [128, 0, 0],
+                [255, 96, 0],
+                [255, 220, 64],
+                [255, 255, 255],
+            ],
+            "ocean" => &[
+                [0, 7, 100],
+                [32, 107, 203],
+                [237, 255, 255],
+                [255, 170, 0],
+                [0, 2, 0],
+            ],
+            "rainbow" => &[
+                [148, 0, 211],
+                [0, 0, 255],
+                [0, 255, 0],
+                [255, 255, 0],
+                [255, 127, 0],
+                [255, 0, 0],
+            ],
+            _ => return None,
+        };
+        Some(Palette::evenly_spaced(colours))
+    }
+
+    /// Parse a gradient file: one colour per line, as `#rrggbb`, optionally
+    /// preceded by its position between 0 and 1. Colours without positions
+    /// are spread evenly. Blank lines are ignored, and so are comments:
+    /// lines starting with a `#` that is not followed by a hexadecimal digit,
+    /// like `# fire` or `#red`. A line like `#fade` is an invalid colour.
+    pub fn parse(text: &str) -> Result<Palette, String> {
+        let mut stops = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || is_comment(line) {
+                continue;
+            }
+            let error = |what: &str| format!("line {}: {} `{}`", number + 1, what, line);
+            let mut words = line.split_whitespace();
+            let (position, colour) = match (words.next(), words.next(), words.next()) {
+                (Some(colour), None, None) => (None, colour),
+                (Some(position), Some(colour), None) => {
+                    let position = position
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|p| (0.0..=1.0).contains(p))
+                        .ok_or_else(|| error("invalid position"))?;
+                    (Some(position), colour)
+                }
+                _ => return Err(error("expected `[POSITION] #RRGGBB`, got")),
+            };
+            let colour = parse_colour(colour).ok_or_else(|| error("invalid colour"))?;
+            stops.push((position, colour));
+        }
+
+        if stops.is_empty() {
+            return Err("the palette has no colours".to_string());
+        }
+        if stops.iter().all(|(position, _)| position.is_none()) {
+            let colours: Vec<Rgb> = stops.iter().map(|(_, colour)| *colour).collect();
+            return Ok(Palette::evenly_spaced(&colours));
+        }
+        let stops = stops
+            .into_iter()
+            .map(|(position, colour)| position.map(|p| (p, colour)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("either every colour or none must have a position")?;
+        if stops.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+            return Err("positions must be increasing".to_string());
+        }
+        Ok(Palette { stops })
+    }
+
+    /// The name of the palette, if it is one of the built-in `PALETTES`.
+    pub fn name(&self) -> Option<&'static str> {
+        PALETTES
+            .iter()
+            .find(|name| Palette::named(name).as_ref() == Some(self))
+            .copied()
+    }
+
+    /// A built-in palette if `name` is one, the gradient file at that path
+    /// if there is one, or else the gradient itself if `name` has colours
+    /// in it.
+    pub fn load(name: &str) -> Result<Palette, String> {
+        if let Some(palette) = Palette::named(name) {
+            return Ok(palette);
+        }
+        if Path::new(name).exists() {
+            let text = fs::read_to_string(name).map_err(|e| format!("{}: {}", name, e))?;
+            return Palette::parse(&text).map_err(|e| format!("{}: {}", name, e));
+        }
+        if name.contains('#') {
+            return Palette::parse(name);
+        }
+        Err(format!(
+            "unknown palette `{}`: expected one of {} or a gradient file",
+            name,
+            PALETTES.join(", ")
+        ))
+    }
+
+    /// The colour at `t`, interpolated between the two closest stops, with
+    /// `t` clamped to 0..=1.
+    pub fn colour(&self, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let next = self.stops.iter().position(|(position, _)| *position >= t);
+        match next {
+            None => self.stops[self.stops.len() - 1].1,
+            Some(0) => self.stops[0].1,
+            Some(i) => {
+                let (p0, c0) = self.stops[i - 1];
+                let (p1, c1) = self.stops[i];
+                let f = if p1 > p0 { (t - p0) / (p1 - p0) } else { 1.0 };
+                let mut colour = [0; 3];
+                for k in 0..3 {
+                    colour[k] = (c0[k] as f64 + (c1[k] as f64 - c0[k] as f64) * f).round() as u8;
+                }
+                colour
+            }
+        }
+    }
+}
+
+/// Whether the gradient file line `line` is a comment: a `#` which does not
+/// start a colour.
+fn is_comment(line: &str) -> bool {
+    match line.strip_prefix('#') {
+        Some(rest) => !rest.starts_with(|c: char| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+fn parse_colour(s: &str) -> Option<Rgb> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+impl fmt::Display for Palette {
+    /// Write the palette as a gradient file, which `parse` reads back.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (position, [r, g, b]) in &self.stops {
+            writeln!(f, "{} #{:02x}{:02x}{:02x}", position, r, g, b)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_palette() {
+    let grayscale = Palette::named("grayscale").unwrap();
+    assert_eq!(grayscale.colour(0.0), [255, 255, 255]);
+    assert_eq!(grayscale.colour(1.0), [0, 0, 0]);
+    assert_eq!(grayscale.colour(2.0), [0, 0, 0]);
+    assert_eq!(grayscale.colour(100.0 / 255.0), [155, 155, 155]);
+
+    let palette = Palette::parse("# red to blue\n#ff0000\n\n#0000FF\n").unwrap();
+    assert_eq!(palette.colour(0.5), [128, 0, 128]);
+    let palette = Palette::parse("0 #000000\n0.25 #ffffff\n1 #000000").unwrap();
+    assert_eq!(palette.colour(0.25), [255, 255, 255]);
+    assert_eq!(palette.colour(0.625), [128, 128, 128]);
+
+    assert_eq!(
+        Palette::parse("#ff0000\n0.5 #00ff00"),
+        Err("either every colour or none must have a position".to_string())
+    );
+    assert_eq!(
+        Palette::parse("#ff00"),
+        Err("line 1: invalid colour `#ff00`".to_string())
+    );
+    let commented = "#\n#red to blue\n# 2 stops\n#ff0000\n  #- mid\n#0000ff";
+    assert_eq!(
+        Palette::parse(commented),
+        Palette::parse("#ff0000\n#0000ff")
+    );
+    assert_eq!(
+        Palette::parse("#fade to blue\n#0000ff"),
+        Err("line 1: expected `[POSITION] #RRGGBB`, got `#fade to blue`".to_string())
+    );
+    assert_eq!(
+        Palette::parse("1.5 #ff0000"),
+        Err("line 1: invalid position `1.5 #ff0000`".to_string())
+    );
+    assert_eq!(
+        Palette::parse("0.5 #ff0000\n0.25 #00ff00"),
+        Err("positions must be increasing".to_string())
+    );
+    assert!(Palette::load("no-such-palette").is_err());
+
+    let fire = Palette::named("fire").unwrap();
+    assert_eq!(fire.name(), Some("fire"));
+    assert_eq!(palette.name(), None);
+    assert_eq!(Palette::parse(&palette.to_string()), Ok(palette.clone()));
+    assert_eq!(Palette::load(&fire.to_string()), Ok(fire));
+}
+
+#[test]
+fn test_load_gradient_file_with_hash_in_path() {
+    let path = std::env::temp_dir().join(format!("mandelbrot-test-#{}.txt", std::process::id()));
+    fs::write(&path, "#ff0000\n#0000ff\n").unwrap();
+    let loaded = Palette::load(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, Palette::parse("#ff0000\n#0000ff"));
+}
+
+/// The radius beyond which an orbit is taken to escape, when counting
+/// smoothly. A large radius makes the fractional count continuous.
+pub(crate) const SMOOTH_RADIUS: f64 = 256.0;
+
+/// Like `escape_time`, but return a fractional iteration count that varies
+/// continuously with `c`, removing the bands between iteration counts.
+pub fn smooth_escape_time(c: Complex<f64>, limit: usize) -> Option<f64> {
+    let mut z = Complex { re: 0.0, im: 0.0 };
+    for i in 0..limit {
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr > SMOOTH_RADIUS * SMOOTH_RADIUS {
+            // log2(log|z|) grows by one per iteration once |z| is large, so
+            // subtracting it cancels out the jumps of `i`.
+            let log_modulus = norm_sqr.ln() / 2.0;
+            let nu = (log_modulus / std::f64::consts::LN_2).log2();
+            return Some((i as f64 + 1.0 - nu).max(0.0));
+        }
+        z = z * z + c;
+    }
+    None
+}
+
+#[test]
+fn test_smooth_escape_time() {
+    assert_eq!(smooth_escape_time(Complex { re: 0.0, im: 0.0 }, 100), None);
+    let outside = smooth_escape_time(Complex { re: 0.5, im: 0.5 }, 100).unwrap();
+    assert!(outside > 0.0);
+    // The smooth count stays close to the integer one, and varies slowly.
+    let close = smooth_escape_time(
+        Complex {
+            re: 0.5001,
+            im: 0.5,
+        },
+        100,
+    )
+    .unwrap();
+    assert!((outside - close).abs() < 0.01);
+}
+
+/// What the value of a point measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// How many iterations its orbit takes to escape.
+    Escape,
+    /// How far it is from the set, to be coloured by `colourize_distances`.
+    Distance,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "escape" => Ok(Mode::Escape),
+            "distance" => Ok(Mode::Distance),
+            _ => Err(format!("unknown mode `{}`: expected escape or distance", s)),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Mode::Escape => "escape",
+            Mode::Distance => "distance",
+        })
+    }
+}
+
+/// How to compute the escape value of a point.
+#[derive(Clone, Copy)]
+pub struct Escape<'a> {
+    pub fractal: &'a dyn Fractal,
+    /// The iteration limit.
+    pub limit: usize,
+    /// Whether to count iterations fractionally.
+    pub smooth: bool,
+    pub mode: Mode,
+}
+
+impl Escape<'_> {
+    /// The iteration count of `point`, fractional if `smooth` is set, or its
+    /// distance to the set in `Mode::Distance`; `None` if it is taken to be
+    /// in the set.
+    pub fn value(&self, point: Complex<f64>) -> Option<f64> {
+        if self.mode == Mode::Distance {
+            self.fractal.distance_estimate(point, self.limit)
+        } else if self.smooth {
+            self.fractal.smooth_escape_time(point, self.limit)
+        } else {
+            self.fractal
+                .escape_time(point, self.limit)
+                .map(|count| count as f64)
+        }
+    }
+}
+
+/// Compute the escape values of the rows starting at row `top` into `band`,
+/// as `render_rows` does for grayscale pixels.
+pub fn escape_values(
+    band: &mut [Option<f64>],
+    bounds: (usize, usize),
+    top: usize,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    escape: &Escape,
+) {
+    for (i, row) in band.chunks_exact_mut(bounds.0).enumerate() {
+        for (column, value) in row.iter_mut().enumerate() {
+            *value = escape.value(pixel_to_point(
+                bounds,
+                (column, top + i),
+                upper_left,
+                lower_right,
+            ));
+        }
+    }
+}
+
+/// Colour `values`, computed by `escape_values` with iteration limit `limit`,
+/// through `palette`. Points of the set are black.
+///
+/// Without equalisation, a value is placed on the palette in proportion to
+/// `limit`. With it, values are spread according to how many pixels escape
+/// before them, so that the whole palette is used whatever the view.
+pub fn colourize(
+    values: &[Option<f64>],
+    limit: usize,
+    palette: &Palette,
+    equalize: bool,
+) -> Vec<u8> {
+    let colours = EscapeColours::new(values, limit, palette, equalize);
+    let mut pixels = Vec::with_capacity(values.len() * 3);
+    for value in values {
+        pixels.extend_from_slice(&colours.colour(*value));
+    }
+    pixels
+}
+
+/// The colours `colourize` gives escape values, to colour them one at a
+/// time.
+pub struct EscapeColours<'a> {
+    palette: &'a Palette,
+    limit: usize,
+    /// The cumulative histogram to equalise with, if any.
+    cdf: Option<Vec<f64>>,
+}
+
+impl<'a> EscapeColours<'a> {
+    /// The colours of escape values with iteration limit `limit`, equalised
+    /// with the histogram of `values` if `equalize` is set.
+    pub fn new(
+        values: &[Option<f64>],
+        limit: usize,
+        palette: &'a Palette,
+        equalize: bool,
+    ) -> EscapeColours<'a> {
+        EscapeColours {
+            palette,
+            limit,
+            cdf: equalize.then(|| cumulative_histogram(values, limit)),
+        }
+    }
+
+    pub fn colour(&self, value: Option<f64>) -> Rgb {
+        let value = match value {
+            None => return [0, 0, 0],
+            Some(value) => value,
+        };
+        let position = match &self.cdf {
+            Some(cdf) => {
+                let count = (value.floor() as usize).min(self.limit - 1);
+                let fraction = value - count as f64;
+                cdf[count] + (cdf[count + 1] - cdf[count]) * fraction.min(1.0)
+            }
+            None => value / self.limit as f64,
+        };
+        self.palette.colour(position)
+    }
+}
+
+/// Colour `distances` to the set, computed by `escape_values` in
+/// `Mode::Distance`, through `palette`, for pixels `pixel_width` wide.
+///
+/// Points on the boundary of the set take the colour at the end of the
+/// palette, fading to that at its start a pixel away, so that the boundary
+/// is drawn as a crisp line. Points of the set are black.
+pub fn colourize_distances(
+    distances: &[Option<f64>],
+    pixel_width: f64,
+    palette: &Palette,
+) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(distances.len() * 3);
+    for distance in distances {
+        pixels.extend_from_slice(&distance_colour(*distance, pixel_width, palette));
+    }
+    pixels
+}
+
+/// The colour `colourize_distances` gives `distance`.
+pub fn distance_colour(distance: Option<f64>, pixel_width: f64, palette: &Palette) -> Rgb {
+    match distance {
+        None => [0, 0, 0],
+        Some(distance) => palette.colour(1.0 - distance / pixel_width),
+    }
+}
+
+#[test]
+fn test_colourize_distances() {
+    let grayscale = Palette::named("grayscale").unwrap();
+    let distances = [Some(0.0), Some(0.05), Some(0.5), None];
+    assert_eq!(
+        colourize_distances(&distances, 0.1, &grayscale),
+        [0, 0, 0, 128, 128, 128, 255, 255, 255, 0, 0, 0]
+    );
+}
+
+/// For each iteration count `i` up to `limit`, the fraction of the escaping
+/// points that escape in fewer than `i` iterations.
+fn cumulative_histogram(values: &[Option<f64>], limit: usize) -> Vec<f64> {
+    let mut histogram = vec![0_usize; limit + 1];
+    for value in values.iter().flatten() {
+        histogram[(value.floor() as usize).min(limit - 1) + 1] += 1;
+    }
+    let total = histogram.iter().sum::<usize>().max(1) as f64;
+    let mut running = 0;
+    histogram
+        .iter()
+        .map(|count| {
+            running += count;
+            running as f64 / total
+        })
+        .collect()
+}
+
+#[test]
+fn test_colourize() {
+    let grayscale = Palette::named("grayscale").unwrap();
+    let values = [Some(0.0), Some(100.0), None];
+    assert_eq!(
+        colourize(&values, 255, &grayscale, false),
+        [255, 255, 255, 155, 155, 155, 0, 0, 0]
+    );
+
+    // Counts of 10 and 20 out of 1000 would both be near white, but
+    // equalised they are spread over the palette.
+    let values = [Some(10.0), Some(20.0), Some(20.0), None];
+    assert_eq!(
+        colourize(&values, 1000, &grayscale, true),
+        [255, 255, 255, 170, 170, 170, 170, 170, 170, 0, 0, 0]
+    );
+}
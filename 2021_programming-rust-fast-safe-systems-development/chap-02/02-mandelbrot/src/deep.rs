@@ -0,0 +1,402 @@
+//! Deep zooms into the Mandelbrot set, beyond the precision of `f64`.
+//!
+//! Past a width of about 1e-13, neighbouring pixels map to the same `f64`
+//! point. Only the orbit of the centre of the image, the reference, is
+//! computed with arbitrary precision here; every pixel then iterates the
+//! difference `δz` between its orbit and the reference, which is small enough
+//! for `f64` (perturbation theory):
+//!
+//! ```text
+//! δz' = 2 Z δz + δz² + δc
+//! ```
+//!
+//! where `Z` is the reference orbit and `δc` the offset of the pixel from the
+//! centre. When `δz` stops being small compared to `Z + δz`, the pixel is
+//! rebased onto the start of the reference orbit, which avoids the glitches of
+//! plain perturbation. The first iterations, while every `δz` is still a
+//! smooth function of `δc`, are skipped by evaluating a cubic series in `δc`
+//! (series approximation).
+
+use num::bigint::BigInt;
+use num::{Complex, ToPrimitive, Zero};
+
+use crate::colour::SMOOTH_RADIUS;
+use crate::pixel_to_point;
+
+/// Bits of precision kept beyond those needed to tell pixels apart.
+const GUARD_BITS: u64 = 64;
+
+/// How small the cubic term of the series must stay compared to the linear
+/// one for the series to be used.
+const SERIES_TOLERANCE: f64 = 1e-12;
+
+/// A deep view of the Mandelbrot set: its centre, in decimal so as to keep
+/// every digit, and its width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepView {
+    pub center_re: String,
+    pub center_im: String,
+    pub width: f64,
+}
+
+impl DeepView {
+    /// Parse `RE,IM` as the centre of a view of width `width`.
+    pub fn parse(center: &str, width: f64) -> Result<DeepView, String> {
+        let invalid = || format!("invalid centre `{}`", center);
+        let (re, im) = center.split_once(',').ok_or_else(invalid)?;
+        if parse_fixed(re, 0).is_none() || parse_fixed(im, 0).is_none() {
+            return Err(invalid());
+        }
+        if !(width.is_finite() && width > 0.0) {
+            return Err(format!("invalid width `{}`", width));
+        }
+        Ok(DeepView {
+            center_re: re.to_string(),
+            center_im: im.to_string(),
+            width,
+        })
+    }
+
+    /// The centre, to `f64` precision.
+    pub fn center(&self) -> Complex<f64> {
+        Complex {
+            re: self.center_re.parse().unwrap_or_default(),
+            im: self.center_im.parse().unwrap_or_default(),
+        }
+    }
+
+    /// The corners of the view of an image of size `bounds`, relative to the
+    /// centre: the extent of `δc`.
+    pub fn offsets(&self, bounds: (usize, usize)) -> (Complex<f64>, Complex<f64>) {
+        let height = self.width * bounds.1 as f64 / bounds.0 as f64;
+        (
+            Complex {
+                re: -self.width / 2.0,
+                im: height / 2.0,
+            },
+            Complex {
+                re: self.width / 2.0,
+                im: -height / 2.0,
+            },
+        )
+    }
+
+    /// Compute the reference orbit and series of this view, for an image of
+    /// size `bounds` and iteration limit `limit`.
+    pub fn prepare(&self, bounds: (usize, usize), limit: usize) -> DeepRender {
+        let (upper_left, lower_right) = self.offsets(bounds);
+        let pixel_size = self.width / bounds.0 as f64;
+        let bits = (-pixel_size.log2()).ceil().max(0.0) as u64 + GUARD_BITS;
+        let re = parse_fixed(&self.center_re, bits).expect("a centre checked by `parse`");
+        let im = parse_fixed(&self.center_im, bits).expect("a centre checked by `parse`");
+        let orbit = reference_orbit(&re, &im, bits, limit);
+        let max_offset = upper_left.norm();
+        let series = Series::new(&orbit, max_offset, limit);
+        DeepRender {
+            upper_left,
+            lower_right,
+            orbit,
+            series,
+            max_offset,
+        }
+    }
+}
+
+#[test]
+fn test_deep_view() {
+    let view = DeepView::parse("-0.75,0.1", 0.5).unwrap();
+    assert_eq!(view.center(), Complex { re: -0.75, im: 0.1 });
+    assert_eq!(
+        view.offsets((100, 50)),
+        (
+            Complex {
+                re: -0.25,
+                im: 0.125
+            },
+            Complex {
+                re: 0.25,
+                im: -0.125
+            }
+        )
+    );
+    assert_eq!(
+        DeepView::parse("-0.75", 0.5),
+        Err("invalid centre `-0.75`".to_string())
+    );
+    assert_eq!(
+        DeepView::parse("-0.75,0.1", 0.0),
+        Err("invalid width `0`".to_string())
+    );
+}
+
+/// Parse the decimal number `s` as a fixed-point number with `bits`
+/// fractional bits, that is, `s * 2^bits` rounded towards zero.
+pub fn parse_fixed(s: &str, bits: u64) -> Option<BigInt> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let mantissa: BigInt = format!("0{}{}", integer, fraction).parse().ok()?;
+    let scaled = (mantissa << bits) / BigInt::from(10).pow(fraction.len() as u32);
+    Some(if negative { -scaled } else { scaled })
+}
+
+/// The value of the fixed-point number `x`, with `bits` fractional bits.
+fn fixed_to_f64(x: &BigInt, bits: u64) -> f64 {
+    // Drop the bits `f64` cannot hold anyway, so the integer stays in range.
+    let shift = bits.saturating_sub(900);
+    let x = (x >> shift).to_f64().unwrap_or(f64::NAN);
+    x * 2f64.powi(-((bits - shift) as i32))
+}
+
+#[test]
+fn test_parse_fixed() {
+    assert_eq!(parse_fixed("1.5", 4), Some(BigInt::from(24)));
+    assert_eq!(parse_fixed("-0.25", 8), Some(BigInt::from(-64)));
+    assert_eq!(parse_fixed("+3", 1), Some(BigInt::from(6)));
+    assert_eq!(parse_fixed(".5", 1), Some(BigInt::from(1)));
+    for invalid in ["", "-", ".", "1e5", "1.2.3", "0x1"] {
+        assert_eq!(parse_fixed(invalid, 8), None);
+    }
+
+    // A difference of 1e-100 survives, which it would not in `f64`.
+    let bits = 400;
+    let a = parse_fixed(&format!("0.{}1", "0".repeat(99)), bits).unwrap();
+    let b = parse_fixed(&format!("0.{}2", "0".repeat(99)), bits).unwrap();
+    let difference = fixed_to_f64(&(b - a), bits);
+    assert!((difference / 1e-100 - 1.0).abs() < 1e-12);
+    assert_eq!(
+        fixed_to_f64(&parse_fixed("-1.75", bits).unwrap(), bits),
+        -1.75
+    );
+}
+
+/// The orbit of the point `re + i im` (fixed point with `bits` fractional
+/// bits) under `z * z + c` from zero, rounded to `f64`. It stops after
+/// `limit` iterations, or at the first point beyond the smooth colouring
+/// radius.
+fn reference_orbit(re: &BigInt, im: &BigInt, bits: u64, limit: usize) -> Vec<Complex<f64>> {
+    let (mut z_re, mut z_im) = (BigInt::zero(), BigInt::zero());
+    let mut orbit = Vec::with_capacity(limit + 1);
+    for _ in 0..=limit {
+        let z = Complex {
+            re: fixed_to_f64(&z_re, bits),
+            im: fixed_to_f64(&z_im, bits),
+        };
+        orbit.push(z);
+        if z.norm_sqr() > SMOOTH_RADIUS * SMOOTH_RADIUS {
+            break;
+        }
+        let re_squared = (&z_re * &z_re) >> bits;
+        let im_squared = (&z_im * &z_im) >> bits;
+        let product = (&z_re * &z_im) >> (bits - 1);
+        z_re = re_squared - im_squared + re;
+        z_im = product + im;
+    }
+    orbit
+}
+
+/// The cubic approximation `δz ≈ a t + b t² + c t³` of the orbits after
+/// `skip` iterations, where `t` is `δc` divided by the largest offset of the
+/// view. Scaling by the largest offset keeps the coefficients within the
+/// range of `f64` however deep the zoom.
+#[derive(Debug, Clone, Copy)]
+struct Series {
+    skip: usize,
+    a: Complex<f64>,
+    b: Complex<f64>,
+    c: Complex<f64>,
+}
+
+impl Series {
+    fn new(orbit: &[Complex<f64>], max_offset: f64, limit: usize) -> Series {
+        let zero = Complex::zero();
+        let mut series = Series {
+            skip: 0,
+            a: zero,
+            b: zero,
+            c: zero,
+        };
+        // Stop before the end of the orbit, so that pixels can go on from it.
+        while series.skip + 1 < orbit.len().min(limit) {
+            let z = orbit[series.skip];
+            let a = 2.0 * z * series.a + max_offset;
+            let b = 2.0 * z * series.b + series.a * series.a;
+            let c = 2.0 * z * series.c + 2.0 * series.a * series.b;
+            let accurate = c.norm() <= SERIES_TOLERANCE * a.norm();
+            // No pixel may escape within the skipped iterations.
+            let small = (orbit[series.skip + 1].norm() + a.norm() + b.norm() + c.norm()) < 2.0;
+            if !(accurate && small) {
+                break;
+            }
+            series = Series {
+                skip: series.skip + 1,
+                a,
+                b,
+                c,
+            };
+        }
+        series
+    }
+
+    fn evaluate(&self, t: Complex<f64>) -> Complex<f64> {
+        ((self.c * t + self.b) * t + self.a) * t
+    }
+}
+
+/// A deep view ready to render: its reference orbit and series.
+pub struct DeepRender {
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    orbit: Vec<Complex<f64>>,
+    series: Series,
+    max_offset: f64,
+}
+
+impl DeepRender {
+    /// The iterations all pixels skip thanks to the series approximation.
+    pub fn skipped_iterations(&self) -> usize {
+        self.series.skip
+    }
+
+    /// The escape value of the point at offset `dc` from the centre, as
+    /// `colour::Escape::value` computes it for the Mandelbrot set.
+    pub fn value(&self, dc: Complex<f64>, limit: usize, smooth: bool) -> Option<f64> {
+        self.iterate(dc, limit, smooth, self.series)
+    }
+
+    fn iterate(&self, dc: Complex<f64>, limit: usize, smooth: bool, series: Series) -> Option<f64> {
+        let radius = if smooth { SMOOTH_RADIUS } else { 2.0 };
+        let mut n = series.skip;
+        let mut m = series.skip;
+        let mut dz = series.evaluate(dc / self.max_offset);
+        while n < limit {
+            let z = self.orbit[m] + dz;
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > radius * radius {
+                if !smooth {
+                    return Some(n as f64);
+                }
+                let log_modulus = norm_sqr.ln() / 2.0;
+                let nu = (log_modulus / std::f64::consts::LN_2).log2();
+                return Some((n as f64 + 1.0 - nu).max(0.0));
+            }
+            // Rebase when the pixel's orbit comes closer to zero than to the
+            // reference, or runs past its end.
+            if norm_sqr < dz.norm_sqr() || m + 1 == self.orbit.len() {
+                dz = z;
+                m = 0;
+            }
+            dz = 2.0 * self.orbit[m] * dz + dz * dz + dc;
+            m += 1;
+            n += 1;
+        }
+        None
+    }
+
+    /// Compute the escape values of the rows starting at row `top` into
+    /// `band`, as `colour::escape_values` does.
+    pub fn escape_values(
+        &self,
+        band: &mut [Option<f64>],
+        bounds: (usize, usize),
+        top: usize,
+        limit: usize,
+        smooth: bool,
+    ) {
+        for (i, row) in band.chunks_exact_mut(bounds.0).enumerate() {
+            for (column, value) in row.iter_mut().enumerate() {
+                let dc =
+                    pixel_to_point(bounds, (column, top + i), self.upper_left, self.lower_right);
+                *value = self.value(dc, limit, smooth);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn deep_values(view: &DeepView, bounds: (usize, usize), limit: usize) -> Vec<Option<f64>> {
+    let render = view.prepare(bounds, limit);
+    let mut values = vec![None; bounds.0 * bounds.1];
+    render.escape_values(&mut values, bounds, 0, limit, false);
+    values
+}
+
+#[test]
+fn test_perturbation_matches_escape_time() {
+    let view = DeepView::parse("-0.7436,0.1318", 0.01).unwrap();
+    let (bounds, limit) = ((64, 48), 500);
+    let deep = deep_values(&view, bounds, limit);
+
+    let (offset_upper_left, offset_lower_right) = view.offsets(bounds);
+    let (upper_left, lower_right) = (
+        view.center() + offset_upper_left,
+        view.center() + offset_lower_right,
+    );
+    let mut matching = 0;
+    for row in 0..bounds.1 {
+        for column in 0..bounds.0 {
+            let point = pixel_to_point(bounds, (column, row), upper_left, lower_right);
+            let expected = crate::escape_time(point, limit).map(|count| count as f64);
+            if deep[row * bounds.0 + column] == expected {
+                matching += 1;
+            }
+        }
+    }
+    // Rounding differs between the two, which may change a count now and
+    // then right at the boundary of an escape.
+    assert!(
+        matching * 100 >= bounds.0 * bounds.1 * 99,
+        "{} pixels match",
+        matching
+    );
+}
+
+#[test]
+fn test_deep_zoom() {
+    // `i` is a Misiurewicz point: the boundary of the set goes through it at
+    // every scale.
+    let view = DeepView::parse("0,1", 1e-30).unwrap();
+    let (bounds, limit) = ((32, 24), 5000);
+    let render = view.prepare(bounds, limit);
+    assert!(render.skipped_iterations() > 0);
+
+    let values = deep_values(&view, bounds, limit);
+    // At this depth `f64` would give a single colour; the perturbed orbits
+    // still tell the pixels apart.
+    let mut distinct: Vec<_> = values.iter().flatten().map(|v| *v as u64).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    assert!(distinct.len() > 10, "{:?}", distinct);
+
+    // Skipping iterations with the series changes (almost) nothing.
+    let no_series = Series {
+        skip: 0,
+        a: Complex::zero(),
+        b: Complex::zero(),
+        c: Complex::zero(),
+    };
+    let mut matching = 0;
+    for row in 0..bounds.1 {
+        for column in 0..bounds.0 {
+            let dc = pixel_to_point(bounds, (column, row), render.upper_left, render.lower_right);
+            if render.iterate(dc, limit, false, no_series) == values[row * bounds.0 + column] {
+                matching += 1;
+            }
+        }
+    }
+    assert!(
+        matching * 100 >= bounds.0 * bounds.1 * 99,
+        "{} pixels match",
+        matching
+    );
+}
@@ -0,0 +1,410 @@
+//! The escape-time fractals: the Mandelbrot set and its relatives.
+//!
+//! Each family iterates its own map from a starting point; rendering only
+//! needs to know how to run an orbit, so every family goes through the same
+//! `pixel_to_point` and colouring pipeline.
+
+use std::f64::consts::LN_2;
+use std::fmt;
+use std::str::FromStr;
+
+use num::Complex;
+
+use crate::colour::{smooth_escape_time, SMOOTH_RADIUS};
+use crate::kernel::{escape_time_checked, in_cardioid_or_bulb};
+use crate::parse_complex;
+
+/// A fractal drawn by counting how fast orbits escape.
+pub trait Fractal: Sync {
+    /// The first point of the orbit of the pixel at `point`, and the
+    /// constant `c` of the map.
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>);
+
+    /// The next point of an orbit after `z`.
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64>;
+
+    /// The degree of the map, which sets how fast escaping orbits grow.
+    fn degree(&self) -> f64 {
+        2.0
+    }
+
+    /// A radius beyond which every orbit goes to infinity.
+    fn escape_radius(&self) -> f64 {
+        2.0
+    }
+
+    /// The number of iterations it took the orbit of `point` to leave the
+    /// circle of the escape radius, or `None` if it did not within `limit`.
+    fn escape_time(&self, point: Complex<f64>, limit: usize) -> Option<usize> {
+        let radius = self.escape_radius();
+        let (mut z, c) = self.start(point);
+        for i in 0..limit {
+            if z.norm_sqr() > radius * radius {
+                return Some(i);
+            }
+            z = self.step(z, c);
+        }
+        None
+    }
+
+    /// The derivative of the map at `z`, or `None` if the map is not
+    /// holomorphic, so that distances to the set cannot be estimated.
+    fn derivative(&self, _z: Complex<f64>) -> Option<Complex<f64>> {
+        None
+    }
+
+    /// The derivatives of the first point of an orbit and of `c` with
+    /// respect to the point of the pixel: `0` and `1` when the point is `c`.
+    fn start_derivatives(&self) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 0.0, im: 0.0 }, Complex { re: 1.0, im: 0.0 })
+    }
+
+    /// An estimate of the distance from `point` to the set, or `None` if
+    /// `point` seems to be in the set or distances cannot be estimated.
+    ///
+    /// This is `|z| ln |z| / |dz|` once the orbit has escaped, `dz` being the
+    /// derivative of the orbit with respect to `point`. The true distance is
+    /// at least half the estimate and, close to the set, at most about twice
+    /// it.
+    fn distance_estimate(&self, point: Complex<f64>, limit: usize) -> Option<f64> {
+        let (mut z, c) = self.start(point);
+        let (mut dz, dc) = self.start_derivatives();
+        let radius = SMOOTH_RADIUS.max(self.escape_radius());
+        for _ in 0..limit {
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > radius * radius {
+                let modulus = norm_sqr.sqrt();
+                return Some(modulus * modulus.ln() / dz.norm());
+            }
+            dz = self.derivative(z)? * dz + dc;
+            z = self.step(z, c);
+        }
+        None
+    }
+
+    /// Like `escape_time`, as a fractional count varying continuously with
+    /// `point`.
+    fn smooth_escape_time(&self, point: Complex<f64>, limit: usize) -> Option<f64> {
+        let (mut z, c) = self.start(point);
+        let radius = SMOOTH_RADIUS.max(self.escape_radius());
+        for i in 0..limit {
+            let norm_sqr = z.norm_sqr();
+            if norm_sqr > radius * radius {
+                let log_modulus = norm_sqr.ln() / 2.0;
+                let nu = (log_modulus / LN_2).ln() / self.degree().ln();
+                return Some((i as f64 + 1.0 - nu).max(0.0));
+            }
+            z = self.step(z, c);
+        }
+        None
+    }
+}
+
+/// The Mandelbrot set: `z * z + c` from zero, with `c` the point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mandelbrot;
+
+impl Fractal for Mandelbrot {
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 0.0, im: 0.0 }, point)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        z * z + c
+    }
+
+    fn derivative(&self, z: Complex<f64>) -> Option<Complex<f64>> {
+        Some(2.0 * z)
+    }
+
+    fn escape_time(&self, point: Complex<f64>, limit: usize) -> Option<usize> {
+        escape_time_checked(point, limit)
+    }
+
+    fn smooth_escape_time(&self, point: Complex<f64>, limit: usize) -> Option<f64> {
+        if in_cardioid_or_bulb(point) {
+            return None;
+        }
+        smooth_escape_time(point, limit)
+    }
+}
+
+/// The Julia set of `c`: `z * z + c` from the point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Julia {
+    pub c: Complex<f64>,
+}
+
+impl Fractal for Julia {
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        (point, self.c)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        z * z + c
+    }
+
+    fn derivative(&self, z: Complex<f64>) -> Option<Complex<f64>> {
+        Some(2.0 * z)
+    }
+
+    fn start_derivatives(&self) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 1.0, im: 0.0 }, Complex { re: 0.0, im: 0.0 })
+    }
+
+    /// Past `max(2, |c|)`, `|z * z + c| >= |z| * |z| - |c| > |z|`, and the
+    /// orbit keeps growing.
+    fn escape_radius(&self) -> f64 {
+        self.c.norm().max(2.0)
+    }
+}
+
+/// The Burning Ship: like the Mandelbrot set, with the absolute values of
+/// the parts of `z` squared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurningShip;
+
+impl Fractal for BurningShip {
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 0.0, im: 0.0 }, point)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        let z = Complex {
+            re: z.re.abs(),
+            im: z.im.abs(),
+        };
+        z * z + c
+    }
+}
+
+/// The Tricorn: like the Mandelbrot set, with the conjugate of `z` squared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tricorn;
+
+impl Fractal for Tricorn {
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 0.0, im: 0.0 }, point)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        let z = z.conj();
+        z * z + c
+    }
+}
+
+/// The Multibrot set of degree `degree`: `z^degree + c` from zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Multibrot {
+    pub degree: u32,
+}
+
+impl Fractal for Multibrot {
+    fn start(&self, point: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        (Complex { re: 0.0, im: 0.0 }, point)
+    }
+
+    fn step(&self, z: Complex<f64>, c: Complex<f64>) -> Complex<f64> {
+        z.powu(self.degree) + c
+    }
+
+    fn derivative(&self, z: Complex<f64>) -> Option<Complex<f64>> {
+        Some(self.degree as f64 * z.powu(self.degree - 1))
+    }
+
+    fn degree(&self) -> f64 {
+        self.degree as f64
+    }
+}
+
+#[test]
+fn test_fractals() {
+    use crate::escape_time;
+
+    let point = Complex { re: -0.75, im: 0.1 };
+    for limit in [10, 100, 1000] {
+        assert_eq!(
+            Multibrot { degree: 2 }.escape_time(point, limit),
+            escape_time(point, limit)
+        );
+        // From zero, the orbit of the Julia set of `c` is the orbit of `c` in
+        // the Mandelbrot set.
+        assert_eq!(
+            Julia { c: point }.escape_time(Complex { re: 0.0, im: 0.0 }, limit),
+            escape_time(point, limit)
+        );
+    }
+
+    // With `|c| > 2`, orbits may come back from beyond 2: this one only
+    // escapes from the circle of radius 3.
+    let julia = Julia {
+        c: Complex { re: -3.0, im: 0.0 },
+    };
+    assert_eq!(julia.escape_radius(), 3.0);
+    assert_eq!(
+        julia.escape_time(Complex { re: 2.1, im: 0.0 }, 100),
+        Some(9)
+    );
+
+    // The Tricorn is symmetric about the real axis.
+    let above = Complex { re: -0.3, im: 0.9 };
+    assert_eq!(
+        Tricorn.escape_time(above, 500),
+        Tricorn.escape_time(above.conj(), 500)
+    );
+    // The mast of the Burning Ship, on the negative real axis.
+    assert_eq!(
+        BurningShip.escape_time(Complex { re: -1.75, im: 0.0 }, 500),
+        None
+    );
+    assert_eq!(
+        Multibrot { degree: 3 }.escape_time(Complex { re: 0.3, im: 0.0 }, 500),
+        None
+    );
+    assert!(Multibrot { degree: 3 }
+        .escape_time(Complex { re: 0.8, im: 0.0 }, 500)
+        .is_some());
+
+    let smooth = Multibrot { degree: 3 }.smooth_escape_time(Complex { re: 0.8, im: 0.0 }, 500);
+    let close = Multibrot { degree: 3 }.smooth_escape_time(
+        Complex {
+            re: 0.8001,
+            im: 0.0,
+        },
+        500,
+    );
+    assert!((smooth.unwrap() - close.unwrap()).abs() < 0.01);
+}
+
+#[test]
+fn test_distance_estimate() {
+    // -2 is the leftmost point of the Mandelbrot set.
+    let distance = Mandelbrot
+        .distance_estimate(Complex { re: -2.1, im: 0.0 }, 1000)
+        .unwrap();
+    assert!(distance > 0.1 / 2.0 && distance < 0.1 * 2.0, "{}", distance);
+    let far = Mandelbrot
+        .distance_estimate(Complex { re: 2.0, im: 2.0 }, 1000)
+        .unwrap();
+    assert!(far > distance);
+    assert_eq!(
+        Mandelbrot.distance_estimate(Complex { re: -0.5, im: 0.0 }, 1000),
+        None
+    );
+
+    // The same orbits, from the other side.
+    let julia = Julia {
+        c: Complex { re: 0.0, im: 0.0 },
+    };
+    // The Julia set of 0 is the unit circle.
+    let distance = julia
+        .distance_estimate(Complex { re: 1.1, im: 0.0 }, 1000)
+        .unwrap();
+    assert!(distance > 0.1 / 2.0 && distance < 0.1 * 2.0, "{}", distance);
+    assert_eq!(
+        Multibrot { degree: 2 }.distance_estimate(Complex { re: 0.3, im: 0.0 }, 1000),
+        Mandelbrot.distance_estimate(Complex { re: 0.3, im: 0.0 }, 1000)
+    );
+    assert_eq!(
+        Tricorn.distance_estimate(Complex { re: 2.0, im: 2.0 }, 1000),
+        None
+    );
+}
+
+/// The fractal families the renderer knows, as chosen on the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Family {
+    Mandelbrot,
+    Julia(Julia),
+    BurningShip,
+    Tricorn,
+    Multibrot(Multibrot),
+}
+
+impl Family {
+    /// The fractal to render.
+    pub fn fractal(&self) -> &dyn Fractal {
+        match self {
+            Family::Mandelbrot => &Mandelbrot,
+            Family::Julia(julia) => julia,
+            Family::BurningShip => &BurningShip,
+            Family::Tricorn => &Tricorn,
+            Family::Multibrot(multibrot) => multibrot,
+        }
+    }
+}
+
+impl FromStr for Family {
+    type Err = String;
+
+    /// Parse `mandelbrot`, `julia:RE,IM`, `burning-ship`, `tricorn` or
+    /// `multibrot:DEGREE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = match s.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (s, None),
+        };
+        match (name, parameter) {
+            ("mandelbrot", None) => Ok(Family::Mandelbrot),
+            ("burning-ship", None) => Ok(Family::BurningShip),
+            ("tricorn", None) => Ok(Family::Tricorn),
+            ("julia", Some(c)) => parse_complex(c)
+                .map(|c| Family::Julia(Julia { c }))
+                .ok_or_else(|| format!("invalid Julia constant `{}`", c)),
+            ("multibrot", Some(degree)) => match degree.parse() {
+                Ok(degree) if degree >= 2 => Ok(Family::Multibrot(Multibrot { degree })),
+                _ => Err(format!("invalid Multibrot degree `{}`", degree)),
+            },
+            _ => Err(format!(
+                "unknown fractal `{}`: expected mandelbrot, julia:RE,IM, \
+                 burning-ship, tricorn or multibrot:DEGREE",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Family {
+    /// Write the family as `from_str` parses it.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Family::Mandelbrot => write!(f, "mandelbrot"),
+            Family::Julia(Julia { c }) => write!(f, "julia:{},{}", c.re, c.im),
+            Family::BurningShip => write!(f, "burning-ship"),
+            Family::Tricorn => write!(f, "tricorn"),
+            Family::Multibrot(Multibrot { degree }) => write!(f, "multibrot:{}", degree),
+        }
+    }
+}
+
+#[test]
+fn test_parse_family() {
+    assert_eq!("mandelbrot".parse(), Ok(Family::Mandelbrot));
+    assert_eq!(
+        "julia:-0.8,0.156".parse(),
+        Ok(Family::Julia(Julia {
+            c: Complex {
+                re: -0.8,
+                im: 0.156
+            }
+        }))
+    );
+    assert_eq!("burning-ship".parse(), Ok(Family::BurningShip));
+    assert_eq!("tricorn".parse(), Ok(Family::Tricorn));
+    assert_eq!(
+        "multibrot:3".parse(),
+        Ok(Family::Multibrot(Multibrot { degree: 3 }))
+    );
+    for family in ["mandelbrot", "julia:-0.8,0.156", "tricorn", "multibrot:5"] {
+        assert_eq!(family.parse::<Family>().unwrap().to_string(), family);
+    }
+    assert_eq!(
+        "multibrot:1".parse::<Family>(),
+        Err("invalid Multibrot degree `1`".to_string())
+    );
+    assert_eq!(
+        "julia".parse::<Family>().unwrap_err(),
+        "unknown fractal `julia`: expected mandelbrot, julia:RE,IM, \
+         burning-ship, tricorn or multibrot:DEGREE"
+    );
+}
@@ -0,0 +1,289 @@
+//! Plotting the Mandelbrot set.
+
+pub mod animation;
+pub mod colour;
+pub mod deep;
+pub mod fractal;
+pub mod kernel;
+pub mod parallel;
+pub mod sampling;
+pub mod tiles;
+
+pub use image::ColorType;
+use kernel::escape_times;
+use num::Complex;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+
+/// Try to determine if `c` is in the Mandelbrot set, using at most `limit`
+/// iterations to decide.
+///
+/// If `c` is not a member, return `Some(i)`, where `i` is the number of
+/// iterations it took for `c` to leave the circle of radius 2 centered on the
+/// origin. If `c` seems to be a member (more precisely, if we reached the
+/// iteration limit without being able to prove that `c` is not a member),
+/// return `None`.
+///
+/// The `kernel` module has faster ways of computing the same thing.
+pub fn escape_time(c: Complex<f64>, limit: usize) -> Option<usize> {
+    let mut z = Complex { re: 0.0, im: 0.0 };
+    for i in 0..limit {
+        if z.norm_sqr() > 4.0 {
+            return Some(i);
+        }
+        z = z * z + c;
+    }
+    None
+}
+
+use std::str::FromStr;
+/// Parse the string `s` as a coordinate pair, like `"400x600"` or `"1.0,0.5"`.
+///
+/// Specifically, `s` should have the form <left><sep><right>, where <sep> is
+/// the character given by the `separator` argument, and <left> and <right> are
+/// both strings that can be parsed by `T::from_str`. `separator` must be an
+/// ASCII character.
+///
+/// If `s` has the proper form, return `Some<(x, y)>`. If it doesn't parse
+/// correctly, return `None`.
+pub fn parse_pair<T: FromStr>(s: &str, separator: char) -> Option<(T, T)> {
+    match s.find(separator) {
+        None => None,
+        Some(index) => match (T::from_str(&s[..index]), T::from_str(&s[index + 1..])) {
+            (Ok(l), Ok(r)) => Some((l, r)),
+            _ => None,
+        },
+    }
+}
+#[test]
+fn test_parse_pair() {
+    assert_eq!(parse_pair::<i32>("", ','), None);
+    assert_eq!(parse_pair::<i32>("10,", ','), None);
+    assert_eq!(parse_pair::<i32>(",10", ','), None);
+    assert_eq!(parse_pair::<i32>("10,20", ','), Some((10, 20)));
+    assert_eq!(parse_pair::<i32>("10,20xy", ','), None);
+    assert_eq!(parse_pair::<f64>("0.5x", 'x'), None);
+    assert_eq!(parse_pair::<f64>("0.5x1.5", 'x'), Some((0.5, 1.5)));
+}
+
+/// Parse a pair of floating-point numbers separated by a comma as a complex
+/// number.
+#[allow(clippy::manual_map)]
+pub fn parse_complex(s: &str) -> Option<Complex<f64>> {
+    match parse_pair(s, ',') {
+        Some((re, im)) => Some(Complex { re, im }),
+        None => None,
+    }
+}
+#[test]
+fn test_parse_complex() {
+    assert_eq!(
+        parse_complex("1.25,-0.0625"),
+        Some(Complex {
+            re: 1.25,
+            im: -0.0625
+        })
+    );
+    assert_eq!(parse_complex(",-0.0625"), None);
+}
+
+/// Given the row and column of a pixel in the output image, return the
+/// corresponding point on the complex plane.
+///
+/// `bounds` is a pair giving the width and height of the image in pixels.
+/// `pixel` is a (column, row) pair indicating a particular pixel in that image.
+/// The `upper_left` and `lower_right` parameters are points on the complex
+/// plane designating the area our image covers.
+pub fn pixel_to_point(
+    bounds: (usize, usize),
+    pixel: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) -> Complex<f64> {
+    let (width, height) = (
+        lower_right.re - upper_left.re,
+        upper_left.im - lower_right.im,
+    );
+    Complex {
+        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
+        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64, // Why subtraction here? pixel.1 increases as we go down,
+                                                                       // but the imaginary component increases as we go up.
+    }
+}
+
+/// Like `pixel_to_point`, for a position `pixel` that may lie between the
+/// corners of pixels.
+pub fn subpixel_to_point(
+    bounds: (usize, usize),
+    pixel: (f64, f64),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) -> Complex<f64> {
+    let (width, height) = (
+        lower_right.re - upper_left.re,
+        upper_left.im - lower_right.im,
+    );
+    Complex {
+        re: upper_left.re + pixel.0 * width / bounds.0 as f64,
+        im: upper_left.im - pixel.1 * height / bounds.1 as f64,
+    }
+}
+
+#[test]
+fn test_pixel_to_point() {
+    assert_eq!(
+        pixel_to_point(
+            (100, 200),
+            (25, 175),
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }
+        ),
+        Complex {
+            re: -0.5,
+            im: -0.75
+        }
+    );
+    assert_eq!(
+        subpixel_to_point(
+            (100, 200),
+            (25.5, 175.0),
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }
+        ),
+        Complex {
+            re: -0.49,
+            im: -0.75
+        }
+    );
+}
+
+/// Render a rectangle of the Mandelbrot set into a buffer of pixels.
+///
+/// The `bounds` argument gives the width and height of the buffer `pixels`,
+/// which holds one grayscale pixel per byte. The `upper_left` and `lower_right`
+/// arguments specify points on the complex plane corresponding to the upper-
+/// left and lower-right corners of the pixel buffer.
+pub fn render(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) {
+    assert!(pixels.len() == bounds.0 * bounds.1);
+    render_rows(pixels, bounds, 0, upper_left, lower_right);
+}
+
+/// Render the rows of the image given by `bounds`, `upper_left` and
+/// `lower_right` starting at row `top` into `band`, which holds as many whole
+/// rows as fit.
+///
+/// Points are computed from the bounds of the whole image, so that rendering
+/// an image band by band gives exactly the pixels of `render`.
+pub fn render_rows(
+    band: &mut [u8],
+    bounds: (usize, usize),
+    top: usize,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) {
+    let shade = |time: Option<usize>| match time {
+        None => 0,
+        Some(count) => 255 - count as u8,
+    };
+    let mut points = vec![Complex::default(); bounds.0];
+    let mut times = vec![None; bounds.0];
+    for (i, row) in band.chunks_exact_mut(bounds.0).enumerate() {
+        for (column, point) in points.iter_mut().enumerate() {
+            *point = pixel_to_point(bounds, (column, top + i), upper_left, lower_right);
+        }
+        escape_times(&points, 255, &mut times);
+        for (pixel, time) in row.iter_mut().zip(&times) {
+            *pixel = shade(*time);
+        }
+    }
+}
+
+/// Write the buffer `pixels`, whose dimensions are given by `bounds`, to the
+/// file named `filename`. `color` says how pixels are laid out in the buffer:
+/// `ColorType::Gray(8)` for `render`, `ColorType::RGB(8)` for
+/// `colour::colourize`.
+///
+/// Each pair of `metadata` is stored as a PNG `tEXt` chunk, with the first
+/// as the keyword: `read_metadata` gets them back.
+pub fn write_image(
+    filename: &str,
+    pixels: &[u8],
+    bounds: (usize, usize),
+    color: ColorType,
+    metadata: &[(String, String)],
+) -> Result<(), std::io::Error> {
+    let output = BufWriter::new(File::create(filename)?);
+    encode(output, pixels, bounds, color, metadata)
+}
+
+/// Encode the buffer `pixels`, whose dimensions are given by `bounds`, as a
+/// PNG file in memory.
+pub fn encode_png(
+    pixels: &[u8],
+    bounds: (usize, usize),
+    color: ColorType,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut png = Vec::new();
+    encode(&mut png, pixels, bounds, color, &[])?;
+    Ok(png)
+}
+
+fn encode<W: Write>(
+    output: W,
+    pixels: &[u8],
+    bounds: (usize, usize),
+    color: ColorType,
+    metadata: &[(String, String)],
+) -> Result<(), std::io::Error> {
+    let color = match color {
+        ColorType::Gray(8) => png::ColorType::Grayscale,
+        ColorType::RGB(8) => png::ColorType::Rgb,
+        color => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot write {:?} pixels", color),
+            ))
+        }
+    };
+    let mut encoder = png::Encoder::new(output, bounds.0 as u32, bounds.1 as u32);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in metadata {
+        encoder.add_text_chunk(keyword.clone(), text.clone())?;
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// The `tEXt` chunks of the PNG file named `filename`, as keyword and text.
+pub fn read_metadata(filename: &str) -> Result<Vec<(String, String)>, std::io::Error> {
+    let decoder = png::Decoder::new(BufReader::new(File::open(filename)?));
+    let reader = decoder.read_info()?;
+    Ok(reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect())
+}
+
+#[test]
+fn test_metadata() {
+    let filename = std::env::temp_dir().join(format!("mandelbrot-test-{}.png", std::process::id()));
+    let filename = filename.to_str().unwrap();
+    let metadata = [
+        ("mandelbrot:limit".to_string(), "255".to_string()),
+        ("mandelbrot:size".to_string(), "2x1".to_string()),
+    ];
+    write_image(filename, &[0, 255], (2, 1), ColorType::Gray(8), &metadata).unwrap();
+    let read = read_metadata(filename);
+    std::fs::remove_file(filename).unwrap();
+    assert_eq!(read.unwrap(), metadata);
+}
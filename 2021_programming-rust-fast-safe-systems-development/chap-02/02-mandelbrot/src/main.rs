@@ -1,159 +1,965 @@
-use image::png::PNGEncoder;
-use image::ColorType;
+mod scene;
+mod server;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use mandelbrot::animation::{frame_filename, Easing, GifWriter, Zoom};
+use mandelbrot::colour::{distance_colour, escape_values, Escape, EscapeColours, Mode, Palette};
+use mandelbrot::deep::DeepView;
+use mandelbrot::fractal::Family;
+use mandelbrot::parallel::Renderer;
+use mandelbrot::sampling::sample_colours;
+use mandelbrot::{parse_complex, parse_pair, read_metadata, write_image, ColorType};
 use num::Complex;
-use std::fs::File;
+use scene::Scene;
 
-/// Try to determine if `c` is in the Mandelbrot set, using at most `limit`
-/// iterations to decide.
-///
-/// If `c` is not a member, return `Some(i)`, where `i` is the number of
-/// iterations it took for `c` to leave the circle of radius 2 centered on the
-/// origin. If `c` seems to be a member (more precisely, if we reached the
-/// iteration limit without being able to prove that `c` is not a member),
-/// return `None`.
-fn escape_time(c: Complex<f64>, limit: usize) -> Option<usize> {
-    let mut z = Complex { re: 0.0, im: 0.0 };
-    for i in 0..limit {
-        if z.norm_sqr() > 4.0 {
-            return Some(i);
+const USAGE: &str = "\
+Usage: {} [OPTIONS] FILE PIXELS UPPERLEFT LOWERRIGHT
+       {} [OPTIONS] --center RE,IM --width W FILE PIXELS
+       {} animate [OPTIONS] --target RE,IM --from W --to W --frames N
+                  [--easing linear|ease-in|ease-out|ease-in-out] [--fps N] OUTPUT PIXELS
+       {} serve [OPTIONS] [--address ADDRESS] [--port N] [--cache TILES]
+       {} scene [OPTIONS] SCENE.toml|SCENE.json OUTPUT [PIXELS]
+       {} rerender [OPTIONS] IMAGE OUTPUT [PIXELS]
+
+Options:
+  --renderer serial|bands|rayon
+  --threads N
+  --fractal mandelbrot|julia:RE,IM|burning-ship|tricorn|multibrot:N
+  --limit N
+  --palette NAME|FILE
+  --smooth
+  --equalize
+  --samples N          anti-alias with N×N samples per pixel
+  --mode escape|distance
+
+Example: {} mandel.png 1000x750 -1.20,0.35 -1,0.20";
+
+/// How to compute and colour pixels: the options every command shares.
+#[derive(Debug, Clone, PartialEq)]
+struct Settings {
+    renderer: Renderer,
+    threads: usize,
+    fractal: Family,
+    /// The iteration limit of the escape time.
+    limit: usize,
+    palette: Palette,
+    /// Whether to colour with fractional iteration counts.
+    smooth: bool,
+    /// Whether to equalise the histogram of iteration counts.
+    equalize: bool,
+    /// The square root of the number of samples per pixel.
+    samples: usize,
+    mode: Mode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            renderer: Renderer::Bands,
+            threads: default_threads(),
+            fractal: Family::Mandelbrot,
+            limit: 255,
+            palette: Palette::named("grayscale").expect("a built-in palette"),
+            smooth: false,
+            equalize: false,
+            samples: 1,
+            mode: Mode::Escape,
         }
-        z = z * z + c;
     }
-    None
 }
 
-use std::str::FromStr;
-/// Parse the string `s` as a coordinate pair, like `"400x600"` or `"1.0,0.5"`.
-///
-/// Specifically, `s` should have the form <left><sep><right>, where <sep> is
-/// the character given by the `separator` argument, and <left> and <right> are
-/// both strings that can be parsed by `T::from_str`. `separator` must be an
-/// ASCII character.
-///
-/// If `s` has the proper form, return `Some<(x, y)>`. If it doesn't parse
-/// correctly, return `None`.
-fn parse_pair<T: FromStr>(s: &str, separator: char) -> Option<(T, T)> {
-    match s.find(separator) {
-        None => None,
-        Some(index) => match (T::from_str(&s[..index]), T::from_str(&s[index + 1..])) {
-            (Ok(l), Ok(r)) => Some((l, r)),
-            _ => None,
+impl Settings {
+    /// Render the view of size `bounds` between `upper_left` and
+    /// `lower_right` as RGB pixels, with perturbation if `deep` is given.
+    ///
+    /// With several samples per pixel, each pixel keeps only the average of
+    /// their colours, and an equalised palette is fitted to the histogram
+    /// of one sample per pixel.
+    fn render(
+        &self,
+        bounds: (usize, usize),
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+        deep: Option<&DeepView>,
+    ) -> Vec<u8> {
+        let escape = Escape {
+            fractal: self.fractal.fractal(),
+            limit: self.limit,
+            smooth: self.smooth,
+            mode: self.mode,
+        };
+        let values = (self.samples == 1 || self.equalize)
+            .then(|| self.values(bounds, upper_left, lower_right, deep, &escape));
+        let escape_colours = EscapeColours::new(
+            values.as_deref().unwrap_or_default(),
+            self.limit,
+            &self.palette,
+            self.equalize,
+        );
+        let pixel_width = (lower_right.re - upper_left.re) / bounds.0 as f64;
+        let colour = |value| match self.mode {
+            Mode::Escape => escape_colours.colour(value),
+            Mode::Distance => distance_colour(value, pixel_width, &self.palette),
+        };
+        if let (1, Some(values)) = (self.samples, &values) {
+            return values.iter().flat_map(|&value| colour(value)).collect();
+        }
+
+        let mut pixels = vec![0; bounds.0 * bounds.1 * 3];
+        self.renderer
+            .for_each_band(&mut pixels, bounds.0 * 3, self.threads, |band, top| {
+                sample_colours(
+                    band,
+                    bounds,
+                    top,
+                    upper_left,
+                    lower_right,
+                    self.samples,
+                    &|point| escape.value(point),
+                    &colour,
+                )
+            });
+        pixels
+    }
+
+    /// The escape values of the pixels of the view, one sample each.
+    fn values(
+        &self,
+        bounds: (usize, usize),
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+        deep: Option<&DeepView>,
+        escape: &Escape,
+    ) -> Vec<Option<f64>> {
+        let mut values = vec![None; bounds.0 * bounds.1];
+        match deep {
+            Some(view) => {
+                let deep = view.prepare(bounds, self.limit);
+                self.renderer
+                    .for_each_band(&mut values, bounds.0, self.threads, |band, top| {
+                        deep.escape_values(band, bounds, top, self.limit, self.smooth)
+                    });
+            }
+            None => {
+                self.renderer
+                    .for_each_band(&mut values, bounds.0, self.threads, |band, top| {
+                        escape_values(band, bounds, top, upper_left, lower_right, escape)
+                    });
+            }
+        }
+        values
+    }
+
+    /// Check that the settings can be used together.
+    fn check(&self) -> Result<(), String> {
+        if self.mode == Mode::Distance {
+            if self.equalize {
+                return Err("distances cannot be equalized".into());
+            }
+            if self
+                .fractal
+                .fractal()
+                .derivative(Complex::default())
+                .is_none()
+            {
+                return Err(
+                    "distances can only be estimated to mandelbrot, julia and multibrot fractals"
+                        .into(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The most samples per pixel along each axis.
+const MAX_SAMPLES: usize = 16;
+
+/// The number of threads to render with when `--threads` is not given.
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// The options that take no value.
+const FLAGS: [&str; 2] = ["smooth", "equalize"];
+
+/// Command-line arguments, with the options of `Settings` parsed.
+struct Options<'a> {
+    settings: Settings,
+    /// The options specific to a command, with their values.
+    others: BTreeMap<&'a str, &'a str>,
+    positional: Vec<&'a str>,
+}
+
+impl<'a> Options<'a> {
+    fn parse(args: &'a [String]) -> Result<Options<'a>, String> {
+        let mut settings = Settings::default();
+        let mut others = BTreeMap::new();
+        let mut positional = vec![];
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let option = match arg.strip_prefix("--") {
+                Some(option) => option,
+                None => {
+                    positional.push(arg.as_str());
+                    continue;
+                }
+            };
+            // Flags, without a value: those of `FLAGS`.
+            match option {
+                "smooth" => {
+                    settings.smooth = true;
+                    continue;
+                }
+                "equalize" => {
+                    settings.equalize = true;
+                    continue;
+                }
+                _ => {}
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", option))?;
+            match option {
+                "renderer" => settings.renderer = value.parse()?,
+                "threads" => {
+                    settings.threads = match value.parse() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("invalid thread count `{}`", value)),
+                    }
+                }
+                "limit" => {
+                    settings.limit = match value.parse() {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(format!("invalid iteration limit `{}`", value)),
+                    }
+                }
+                "samples" => {
+                    settings.samples = match value.parse() {
+                        Ok(n) if (1..=MAX_SAMPLES).contains(&n) => n,
+                        _ => return Err(format!("invalid sample count `{}`", value)),
+                    }
+                }
+                "mode" => settings.mode = value.parse()?,
+                "palette" => settings.palette = Palette::load(value)?,
+                "fractal" => settings.fractal = value.parse()?,
+                _ => {
+                    others.insert(option, value.as_str());
+                }
+            }
+        }
+        settings.check()?;
+        Ok(Options {
+            settings,
+            others,
+            positional,
+        })
+    }
+
+    /// Remove the command-specific option `name`, parsing its value as a
+    /// `what`.
+    fn take<T: FromStr>(&mut self, name: &str, what: &str) -> Result<Option<T>, String> {
+        match self.others.remove(name) {
+            None => Ok(None),
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid {} `{}`", what, value)),
+        }
+    }
+
+    /// Check that every option was understood, and that there are
+    /// `expected` positional arguments.
+    fn finish(&self, expected: usize) -> Result<(), String> {
+        if let Some(option) = self.others.keys().next() {
+            return Err(format!("unknown option --{}", option));
+        }
+        if self.positional.len() != expected {
+            return Err(format!(
+                "expected {} arguments, got {}",
+                expected,
+                self.positional.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The most pixels an image may have: 8192 by 8192, or a frame of that size.
+const MAX_PIXELS: usize = 1 << 26;
+
+fn parse_bounds(s: &str) -> Result<(usize, usize), String> {
+    match parse_pair::<usize>(s, 'x') {
+        Some((width, height)) if width > 0 && height > 0 => match width.checked_mul(height) {
+            Some(pixels) if pixels <= MAX_PIXELS => Ok((width, height)),
+            _ => Err(format!(
+                "image dimensions `{}` exceed {} pixels",
+                s, MAX_PIXELS
+            )),
         },
+        _ => Err(format!("invalid image dimensions `{}`", s)),
     }
 }
-#[test]
-fn test_parse_pair() {
-    assert_eq!(parse_pair::<i32>("", ','), None);
-    assert_eq!(parse_pair::<i32>("10,", ','), None);
-    assert_eq!(parse_pair::<i32>(",10", ','), None);
-    assert_eq!(parse_pair::<i32>("10,20", ','), Some((10, 20)));
-    assert_eq!(parse_pair::<i32>("10,20xy", ','), None);
-    assert_eq!(parse_pair::<f64>("0.5x", 'x'), None);
-    assert_eq!(parse_pair::<f64>("0.5x1.5", 'x'), Some((0.5, 1.5)));
+
+/// What to do, as given on the command line.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Still(Args),
+    Animate(AnimateArgs),
+    Serve(ServeArgs),
+    Scene(SceneArgs),
 }
 
-/// Parse a pair of floating-point numbers separated by a comma as a complex
-/// number.
-fn parse_complex(s: &str) -> Option<Complex<f64>> {
-    match parse_pair(s, ',') {
-        Some((re, im)) => Some(Complex { re, im }),
-        None => None,
+/// Parse the command-line arguments `args`, without the program name.
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        Some("animate") => parse_animate_args(&args[1..]).map(Command::Animate),
+        Some("serve") => parse_serve_args(&args[1..]).map(Command::Serve),
+        Some("scene") => parse_scene_args(&args[1..], false).map(Command::Scene),
+        Some("rerender") => parse_scene_args(&args[1..], true).map(Command::Scene),
+        _ => parse_args(args).map(Command::Still),
     }
 }
+
+/// What to render, and where to write it, as given on the command line.
+#[derive(Debug, PartialEq)]
+struct Args {
+    filename: String,
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    settings: Settings,
+    /// The view to render with perturbation, if given by its centre and
+    /// width. `upper_left` and `lower_right` then only approximate it.
+    deep: Option<DeepView>,
+}
+
+/// Parse the arguments of a still image.
+///
+/// On error, return a message saying which argument is wrong.
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut options = Options::parse(args)?;
+    let center: Option<String> = options.take("center", "centre")?;
+    let width: Option<f64> = options.take("width", "width")?;
+    options.finish(if center.is_some() { 2 } else { 4 })?;
+    let args = &options.positional;
+    let bounds = parse_bounds(args[1])?;
+    let (upper_left, lower_right, deep) = match (center, width) {
+        (Some(center), Some(width)) => {
+            if options.settings.fractal != Family::Mandelbrot {
+                return Err("deep zooms are only possible into the Mandelbrot set".into());
+            }
+            if options.settings.samples > 1 || options.settings.mode != Mode::Escape {
+                return Err(
+                    "deep zooms can only be rendered with one escape time per pixel".into(),
+                );
+            }
+            let view = DeepView::parse(&center, width)?;
+            let (upper_left, lower_right) = view.offsets(bounds);
+            (
+                view.center() + upper_left,
+                view.center() + lower_right,
+                Some(view),
+            )
+        }
+        (None, None) => {
+            let upper_left = parse_complex(args[2])
+                .ok_or_else(|| format!("invalid upper left corner `{}`", args[2]))?;
+            let lower_right = parse_complex(args[3])
+                .ok_or_else(|| format!("invalid lower right corner `{}`", args[3]))?;
+            if upper_left.re >= lower_right.re || upper_left.im <= lower_right.im {
+                return Err(
+                    "the upper left corner must be above and left of the lower right one".into(),
+                );
+            }
+            (upper_left, lower_right, None)
+        }
+        _ => return Err("--center and --width go together".into()),
+    };
+    Ok(Args {
+        filename: args[0].to_string(),
+        bounds,
+        upper_left,
+        lower_right,
+        settings: options.settings,
+        deep,
+    })
+}
+
 #[test]
-fn test_parse_complex() {
+fn test_parse_args() {
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
     assert_eq!(
-        parse_complex("1.25,-0.0625"),
-        Some(Complex {
-            re: 1.25,
-            im: -0.0625
+        parse_args(&args(&["mandel.png", "1000x750", "-1.20,0.35", "-1,0.20"])),
+        Ok(Args {
+            filename: "mandel.png".to_string(),
+            bounds: (1000, 750),
+            upper_left: Complex {
+                re: -1.20,
+                im: 0.35
+            },
+            lower_right: Complex { re: -1.0, im: 0.20 },
+            settings: Settings {
+                renderer: Renderer::Bands,
+                threads: default_threads(),
+                fractal: Family::Mandelbrot,
+                limit: 255,
+                palette: Palette::named("grayscale").unwrap(),
+                smooth: false,
+                equalize: false,
+                samples: 1,
+                mode: Mode::Escape,
+            },
+            deep: None,
         })
     );
-    assert_eq!(parse_complex(",-0.0625"), None);
+    assert_eq!(
+        parse_args(&args(&[
+            "--renderer",
+            "rayon",
+            "mandel.png",
+            "--threads",
+            "3",
+            "1000x750",
+            "-1.20,0.35",
+            "-1,0.20"
+        ]))
+        .map(|args| (args.settings.renderer, args.settings.threads)),
+        Ok((Renderer::Rayon, 3))
+    );
+    let parsed = parse_args(&args(&[
+        "--palette",
+        "fire",
+        "--smooth",
+        "--limit",
+        "1000",
+        "--equalize",
+        "mandel.png",
+        "1000x750",
+        "-1.20,0.35",
+        "-1,0.20",
+    ]))
+    .unwrap()
+    .settings;
+    assert_eq!(parsed.palette, Palette::named("fire").unwrap());
+    assert_eq!(parsed.limit, 1000);
+    assert!(parsed.smooth && parsed.equalize);
+    assert_eq!(
+        parse_args(&args(&[
+            "--fractal",
+            "tricorn",
+            "mandel.png",
+            "1000x750",
+            "-1.20,0.35",
+            "-1,0.20",
+        ]))
+        .map(|args| args.settings.fractal),
+        Ok(Family::Tricorn)
+    );
+    let parsed = parse_args(&args(&[
+        "--center",
+        "-0.75,0.1",
+        "--width",
+        "1e-20",
+        "mandel.png",
+        "1000x750",
+    ]))
+    .unwrap();
+    assert_eq!(
+        parsed.deep,
+        Some(DeepView::parse("-0.75,0.1", 1e-20).unwrap())
+    );
+    assert_eq!(parsed.upper_left, Complex { re: -0.75, im: 0.1 });
+    let parsed = parse_args(&args(&[
+        "--samples",
+        "3",
+        "--mode",
+        "distance",
+        "mandel.png",
+        "1000x750",
+        "-1.20,0.35",
+        "-1,0.20",
+    ]))
+    .unwrap()
+    .settings;
+    assert_eq!((parsed.samples, parsed.mode), (3, Mode::Distance));
+    assert_eq!(
+        parse_args(&args(&["--samples", "0", "mandel.png"])),
+        Err("invalid sample count `0`".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&[
+            "--mode",
+            "distance",
+            "--fractal",
+            "tricorn",
+            "mandel.png"
+        ])),
+        Err(
+            "distances can only be estimated to mandelbrot, julia and multibrot fractals"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        parse_args(&args(&[
+            "--samples",
+            "2",
+            "--center",
+            "-0.75,0.1",
+            "--width",
+            "1e-20",
+            "mandel.png",
+            "1000x750",
+        ])),
+        Err("deep zooms can only be rendered with one escape time per pixel".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["--center", "-0.75,0.1", "mandel.png", "1000x750"])),
+        Err("--center and --width go together".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["--threads", "0", "mandel.png"])),
+        Err("invalid thread count `0`".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "--renderer"])),
+        Err("missing value for --renderer".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["--colour", "red", "mandel.png"])),
+        Err("unknown option --colour".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "1000x750"])),
+        Err("expected 4 arguments, got 2".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "1000x0", "-1.20,0.35", "-1,0.20"])),
+        Err("invalid image dimensions `1000x0`".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "9000x9000", "-1.20,0.35", "-1,0.20"])),
+        Err("image dimensions `9000x9000` exceed 67108864 pixels".to_string())
+    );
+    assert_eq!(
+        parse_bounds(&format!("{}x{}", usize::MAX, 2)),
+        Err(format!(
+            "image dimensions `{}x2` exceed 67108864 pixels",
+            usize::MAX
+        ))
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "1000x750", "-1.20", "-1,0.20"])),
+        Err("invalid upper left corner `-1.20`".to_string())
+    );
+    assert_eq!(
+        parse_args(&args(&["mandel.png", "1000x750", "-1,0.20", "-1.20,0.35"])),
+        Err("the upper left corner must be above and left of the lower right one".to_string())
+    );
 }
 
-/// Given the row and column of a pixel in the output image, return the
-/// corresponding point on the complex plane.
-///
-/// `bounds` is a pair giving the width and height of the image in pixels.
-/// `pixel` is a (column, row) pair indicating a particular pixel in that image.
-/// The `upper_left` and `lower_right` parameters are points on the complex
-/// plane designating the area our image covers.
-fn pixel_to_point(
+/// A zoom animation to render, as given on the command line.
+#[derive(Debug, PartialEq)]
+struct AnimateArgs {
+    /// An animated GIF if it ends in `.gif`, or else the name from which
+    /// those of the PNG frames are made.
+    output: String,
     bounds: (usize, usize),
-    pixel: (usize, usize),
-    upper_left: Complex<f64>,
-    lower_right: Complex<f64>,
-) -> Complex<f64> {
-    let (width, height) = (
-        lower_right.re - upper_left.re,
-        upper_left.im - lower_right.im,
+    zoom: Zoom,
+    /// Frames per second of an animated GIF.
+    fps: u32,
+    settings: Settings,
+}
+
+/// Parse the arguments of the `animate` command.
+fn parse_animate_args(args: &[String]) -> Result<AnimateArgs, String> {
+    let mut options = Options::parse(args)?;
+    let target: Option<String> = options.take("target", "target")?;
+    let start_width: Option<f64> = options.take("from", "start width")?;
+    let end_width: Option<f64> = options.take("to", "end width")?;
+    let frames: Option<usize> = options.take("frames", "frame count")?;
+    let easing: Option<Easing> = options.take("easing", "easing")?;
+    let fps: Option<u32> = options.take("fps", "frame rate")?;
+    options.finish(2)?;
+    if options.settings.equalize {
+        return Err("frames cannot be equalized: each would get its own histogram".into());
+    }
+
+    let missing = |name: &str| format!("missing --{}", name);
+    let target = target.ok_or_else(|| missing("target"))?;
+    let target = parse_complex(&target).ok_or_else(|| format!("invalid target `{}`", target))?;
+    let start_width = start_width.ok_or_else(|| missing("from"))?;
+    let end_width = end_width.ok_or_else(|| missing("to"))?;
+    if !(start_width > 0.0 && end_width > 0.0) {
+        return Err("zoom widths must be positive".into());
+    }
+    let frames = frames.ok_or_else(|| missing("frames"))?;
+    if frames == 0 {
+        return Err("invalid frame count `0`".into());
+    }
+    if fps == Some(0) {
+        return Err("invalid frame rate `0`".into());
+    }
+    let bounds = parse_bounds(options.positional[1])?;
+    // Narrower frames would have neighbouring pixels on the same `f64`, and
+    // frames are not rendered with perturbation.
+    let scale = target.re.abs().max(target.im.abs()).max(1.0);
+    let narrowest = scale * f64::EPSILON * 4.0 * bounds.0 as f64;
+    if start_width.min(end_width) < narrowest {
+        return Err(format!(
+            "zoom widths below {:.1e} are too deep for animations",
+            narrowest
+        ));
+    }
+    Ok(AnimateArgs {
+        output: options.positional[0].to_string(),
+        bounds,
+        zoom: Zoom {
+            target,
+            start_width,
+            end_width,
+            frames,
+            easing: easing.unwrap_or(Easing::EaseInOut),
+        },
+        fps: fps.unwrap_or(25),
+        settings: options.settings,
+    })
+}
+
+#[test]
+fn test_parse_animate_args() {
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+    assert_eq!(
+        parse_command(&args(&[
+            "animate",
+            "--target",
+            "-0.75,0.1",
+            "--from",
+            "3",
+            "--to",
+            "1e-6",
+            "--frames",
+            "120",
+            "--palette",
+            "fire",
+            "zoom.gif",
+            "320x240",
+        ])),
+        Ok(Command::Animate(AnimateArgs {
+            output: "zoom.gif".to_string(),
+            bounds: (320, 240),
+            zoom: Zoom {
+                target: Complex { re: -0.75, im: 0.1 },
+                start_width: 3.0,
+                end_width: 1e-6,
+                frames: 120,
+                easing: Easing::EaseInOut,
+            },
+            fps: 25,
+            settings: Settings {
+                palette: Palette::named("fire").unwrap(),
+                ..Settings::default()
+            },
+        }))
+    );
+    assert_eq!(
+        parse_animate_args(&args(&["--target", "-0.75,0.1", "zoom.gif", "320x240"])),
+        Err("missing --from".to_string())
+    );
+    assert_eq!(
+        parse_animate_args(&args(&[
+            "--target", "0,0", "--from", "1", "--to", "2", "--frames", "2", "--easing", "bounce",
+            "zoom.gif", "320x240"
+        ])),
+        Err("invalid easing `bounce`".to_string())
+    );
+    assert_eq!(
+        parse_animate_args(&args(&[
+            "--target", "0,0", "--from", "1", "--to", "2", "--frames", "2", "--fps", "0",
+            "zoom.gif", "320x240"
+        ])),
+        Err("invalid frame rate `0`".to_string())
+    );
+    assert_eq!(
+        parse_animate_args(&args(&[
+            "--target",
+            "-0.75,0.1",
+            "--from",
+            "3",
+            "--to",
+            "1e-13",
+            "--frames",
+            "2",
+            "zoom.gif",
+            "320x240"
+        ])),
+        Err("zoom widths below 2.8e-13 are too deep for animations".to_string())
+    );
+    assert_eq!(
+        parse_animate_args(&args(&["--equalize", "zoom.gif", "320x240"])),
+        Err("frames cannot be equalized: each would get its own histogram".to_string())
     );
-    Complex {
-        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
-        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64, // Why subtraction here? pixel.1 increases as we go down,
-                                                                       // but the imaginary component increases as we go up.
+    assert!(matches!(
+        parse_command(&args(&["mandel.png", "1000x750", "-1.20,0.35", "-1,0.20"])),
+        Ok(Command::Still(_))
+    ));
+}
+
+/// Where the tile server listens, and how it renders, as given on the
+/// command line.
+#[derive(Debug, PartialEq)]
+struct ServeArgs {
+    address: String,
+    port: u16,
+    /// How many tiles to keep in memory.
+    cache: usize,
+    settings: Settings,
+}
+
+/// Parse the arguments of the `serve` command.
+fn parse_serve_args(args: &[String]) -> Result<ServeArgs, String> {
+    let mut options = Options::parse(args)?;
+    let address: Option<String> = options.take("address", "address")?;
+    let port: Option<u16> = options.take("port", "port")?;
+    let cache: Option<usize> = options.take("cache", "cache size")?;
+    options.finish(0)?;
+    if options.settings.equalize {
+        return Err("tiles cannot be equalized: each would get its own histogram".into());
+    }
+    if cache == Some(0) {
+        return Err("invalid cache size `0`".into());
     }
+    Ok(ServeArgs {
+        address: address.unwrap_or_else(|| "127.0.0.1".to_string()),
+        port: port.unwrap_or(3000),
+        cache: cache.unwrap_or(1024),
+        settings: options.settings,
+    })
 }
 
 #[test]
-fn test_pixel_to_point() {
-    assert_eq!(
-        pixel_to_point(
-            (100, 200),
-            (25, 175),
-            Complex { re: -1.0, im: 1.0 },
-            Complex { re: 1.0, im: -1.0 }
-        ),
-        Complex {
-            re: -0.5,
-            im: -0.75
+fn test_parse_serve_args() {
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+    assert_eq!(
+        parse_command(&args(&["serve", "--port", "8080", "--palette", "ocean"])),
+        Ok(Command::Serve(ServeArgs {
+            address: "127.0.0.1".to_string(),
+            port: 8080,
+            cache: 1024,
+            settings: Settings {
+                palette: Palette::named("ocean").unwrap(),
+                ..Settings::default()
+            },
+        }))
+    );
+    assert_eq!(
+        parse_serve_args(&args(&["--port", "65536"])),
+        Err("invalid port `65536`".to_string())
+    );
+    assert_eq!(
+        parse_serve_args(&args(&["--equalize"])),
+        Err("tiles cannot be equalized: each would get its own histogram".to_string())
+    );
+    assert_eq!(
+        parse_serve_args(&args(&["mandel.png"])),
+        Err("expected 0 arguments, got 1".to_string())
+    );
+}
+
+/// A scene to render, from a scene file or the metadata of an image, as
+/// given on the command line.
+#[derive(Debug, PartialEq)]
+struct SceneArgs {
+    source: String,
+    /// Whether `source` is an image rendered earlier, rather than a scene
+    /// file.
+    from_image: bool,
+    output: String,
+    /// The size to render at, if not that of the scene.
+    size: Option<String>,
+    /// Options overriding those of the scene, with their values.
+    options: Vec<String>,
+}
+
+/// Parse the arguments of the `scene` and `rerender` commands.
+fn parse_scene_args(args: &[String], from_image: bool) -> Result<SceneArgs, String> {
+    let mut options = vec![];
+    let mut positional = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(option) => {
+                options.push(arg.clone());
+                if !FLAGS.contains(&option) {
+                    options.extend(args.next().cloned());
+                }
+            }
+            None => positional.push(arg.clone()),
         }
+    }
+    let count = positional.len();
+    let mut positional = positional.into_iter();
+    match (
+        positional.next(),
+        positional.next(),
+        positional.next(),
+        positional.next(),
+    ) {
+        (Some(source), Some(output), size, None) => {
+            if let Some(size) = &size {
+                parse_bounds(size)?;
+            }
+            Ok(SceneArgs {
+                source,
+                from_image,
+                output,
+                size,
+                options,
+            })
+        }
+        _ => Err(format!("expected 2 or 3 arguments, got {}", count)),
+    }
+}
+
+#[test]
+fn test_parse_scene_args() {
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+    assert_eq!(
+        parse_command(&args(&[
+            "rerender",
+            "--smooth",
+            "--samples",
+            "3",
+            "old.png",
+            "new.png",
+            "1600x1200"
+        ])),
+        Ok(Command::Scene(SceneArgs {
+            source: "old.png".to_string(),
+            from_image: true,
+            output: "new.png".to_string(),
+            size: Some("1600x1200".to_string()),
+            options: args(&["--smooth", "--samples", "3"]),
+        }))
+    );
+    assert!(matches!(
+        parse_command(&args(&["scene", "view.toml", "view.png"])),
+        Ok(Command::Scene(SceneArgs {
+            from_image: false,
+            size: None,
+            ..
+        }))
+    ));
+    assert_eq!(
+        parse_scene_args(&args(&["view.toml"]), false),
+        Err("expected 2 or 3 arguments, got 1".to_string())
     );
 }
 
-/// Render a rectangle of the Mandelbrot set into a buffer of pixels.
-///
-/// The `bounds` argument gives the width and height of the buffer `pixels`,
-/// which holds one grayscale pixel per byte. The `upper_left` and `lower_right`
-/// arguments specify points on the complex plane corresponding to the upper-
-/// left and lower-right corners of the pixel buffer.
-fn render(
-    pixels: &mut [u8],
-    bounds: (usize, usize),
-    upper_left: Complex<f64>,
-    lower_right: Complex<f64>,
-) {
-    assert!(pixels.len() == bounds.0 * bounds.1);
-    for row in 0..bounds.1 {
-        for column in 0..bounds.0 {
-            let point = pixel_to_point(bounds, (column, row), upper_left, lower_right);
-            pixels[row * bounds.0 + column] = match escape_time(point, 255) {
-                None => 0,
-                Some(count) => 255 - count as u8,
-            };
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let program = args.first().map(String::as_str).unwrap_or("mandelbrot");
+    let command = match parse_command(args.get(1..).unwrap_or_default()) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}: {}", program, message);
+            eprintln!("{}", USAGE.replace("{}", program));
+            std::process::exit(2);
         }
+    };
+
+    let result = match command {
+        Command::Still(args) => render(&args),
+        Command::Animate(args) => animate(&args),
+        Command::Scene(args) => render_scene(&args),
+        Command::Serve(args) => server::serve(args).map_err(|e| format!("server error: {}", e)),
+    };
+    if let Err(message) = result {
+        eprintln!("{}: {}", program, message);
+        std::process::exit(1);
     }
 }
 
-/// Write the buffer `pixels`, whose dimensions are given by `bounds`, to the
-/// file named `filename`.
-fn write_image(
-    filename: &str,
-    pixels: &[u8],
-    bounds: (usize, usize),
-) -> Result<(), std::io::Error> {
-    let output = File::create(filename)?;
-    let encoder = PNGEncoder::new(output);
-    encoder.encode(
+/// Render a still image, and write it out with its scene.
+fn render(args: &Args) -> Result<(), String> {
+    let pixels = args.settings.render(
+        args.bounds,
+        args.upper_left,
+        args.lower_right,
+        args.deep.as_ref(),
+    );
+    let scene = Scene::new(
+        args.bounds,
+        args.upper_left,
+        args.lower_right,
+        args.deep.as_ref(),
+        &args.settings,
+    );
+    write_image(
+        &args.filename,
         &pixels,
-        bounds.0 as u32,
-        bounds.1 as u32,
-        ColorType::Gray(8),
-    )?;
-    Ok(())
+        args.bounds,
+        ColorType::RGB(8),
+        &scene.metadata(),
+    )
+    .map_err(|e| format!("error writing {}: {}", args.filename, e))
 }
 
-fn main() {
-    println!("Hello, world!");
+/// Render a scene from a file or an image, with the options and size given
+/// on the command line taking precedence.
+fn render_scene(args: &SceneArgs) -> Result<(), String> {
+    let scene = if args.from_image {
+        read_metadata(&args.source)
+            .map_err(|e| e.to_string())
+            .and_then(|metadata| Scene::from_metadata(&metadata))
+    } else {
+        Scene::load(&args.source)
+    };
+    let mut scene = scene.map_err(|e| format!("{}: {}", args.source, e))?;
+    if let Some(size) = &args.size {
+        scene.size = size.clone();
+    }
+    // Later options replace earlier ones.
+    let mut command = scene.args(&args.output);
+    command.extend(args.options.iter().cloned());
+    let still = parse_args(&command).map_err(|e| format!("{}: {}", args.source, e))?;
+    render(&still)
+}
+
+/// Render every frame of a zoom animation, and write them out.
+fn animate(args: &AnimateArgs) -> Result<(), String> {
+    let error = |filename: &str, e: std::io::Error| format!("error writing {}: {}", filename, e);
+    let mut gif = if args.output.ends_with(".gif") {
+        let writer = GifWriter::create(&args.output, args.bounds, args.fps)
+            .map_err(|e| error(&args.output, e))?;
+        Some(writer)
+    } else {
+        None
+    };
+    for frame in 0..args.zoom.frames {
+        let (upper_left, lower_right) = args.zoom.corners(frame, args.bounds);
+        let pixels = args
+            .settings
+            .render(args.bounds, upper_left, lower_right, None);
+        match &mut gif {
+            Some(gif) => gif
+                .write_frame(&pixels)
+                .map_err(|e| error(&args.output, e))?,
+            None => {
+                let filename = frame_filename(&args.output, frame, args.zoom.frames);
+                let scene = Scene::new(args.bounds, upper_left, lower_right, None, &args.settings);
+                write_image(
+                    &filename,
+                    &pixels,
+                    args.bounds,
+                    ColorType::RGB(8),
+                    &scene.metadata(),
+                )
+                .map_err(|e| error(&filename, e))?;
+            }
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,168 @@
+//! Renderers spreading the work of `render` over several threads.
+//!
+//! Each produces exactly the pixels `render` would: they only differ in how
+//! the rows of the image are shared out.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use num::Complex;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+#[cfg(test)]
+use crate::render;
+use crate::render_rows;
+
+/// How to share the rendering of an image between threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// Everything on the calling thread.
+    Serial,
+    /// One horizontal band of rows per thread.
+    Bands,
+    /// One task per row, balanced by Rayon's work stealing.
+    Rayon,
+}
+
+impl FromStr for Renderer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "serial" => Ok(Renderer::Serial),
+            "bands" => Ok(Renderer::Bands),
+            "rayon" => Ok(Renderer::Rayon),
+            _ => Err(format!(
+                "unknown renderer `{}`: expected serial, bands or rayon",
+                s
+            )),
+        }
+    }
+}
+
+impl Renderer {
+    /// Render like `render`, using at most `threads` threads.
+    pub fn render(
+        self,
+        pixels: &mut [u8],
+        bounds: (usize, usize),
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+        threads: usize,
+    ) {
+        assert!(pixels.len() == bounds.0 * bounds.1);
+        self.for_each_band(pixels, bounds.0, threads, |band, top| {
+            render_rows(band, bounds, top, upper_left, lower_right)
+        });
+    }
+
+    /// Split `buffer`, an image `width` pixels wide, into bands of whole rows,
+    /// and call `f` on each band with the index of its first row, using at
+    /// most `threads` threads.
+    pub fn for_each_band<T, F>(self, buffer: &mut [T], width: usize, threads: usize, f: F)
+    where
+        T: Send,
+        F: Fn(&mut [T], usize) + Sync,
+    {
+        let rows = buffer.len() / width.max(1);
+        match self {
+            Renderer::Serial => f(buffer, 0),
+            Renderer::Bands => {
+                let rows_per_band = rows.div_ceil(threads.max(1)).max(1);
+                let f = &f;
+                crossbeam::scope(|spawner| {
+                    for (i, band) in buffer.chunks_mut(rows_per_band * width).enumerate() {
+                        spawner.spawn(move |_| f(band, i * rows_per_band));
+                    }
+                })
+                .expect("a rendering thread panicked");
+            }
+            Renderer::Rayon => {
+                rayon_pool(threads).install(|| {
+                    buffer
+                        .par_chunks_mut(width)
+                        .enumerate()
+                        .for_each(|(row, band)| f(band, row))
+                });
+            }
+        }
+    }
+}
+
+/// The Rayon pool of `threads` threads, started the first time it is asked
+/// for and kept for the next renders.
+fn rayon_pool(threads: usize) -> Arc<ThreadPool> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = OnceLock::new();
+    let mut pools = POOLS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    pools
+        .entry(threads)
+        .or_insert_with(|| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("cannot start the rendering threads");
+            Arc::new(pool)
+        })
+        .clone()
+}
+
+#[test]
+fn test_rayon_pool_is_reused() {
+    assert!(Arc::ptr_eq(&rayon_pool(3), &rayon_pool(3)));
+    assert_eq!(rayon_pool(3).current_num_threads(), 3);
+    assert!(!Arc::ptr_eq(&rayon_pool(3), &rayon_pool(2)));
+}
+
+/// Render the image as `threads` horizontal bands, each on its own
+/// crossbeam scoped thread.
+pub fn render_bands(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    threads: usize,
+) {
+    Renderer::Bands.render(pixels, bounds, upper_left, lower_right, threads)
+}
+
+/// Render the image one row per Rayon task, on a pool of `threads` threads.
+pub fn render_rayon(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    threads: usize,
+) {
+    Renderer::Rayon.render(pixels, bounds, upper_left, lower_right, threads)
+}
+
+#[test]
+fn test_parallel_renderers_match_serial() {
+    let upper_left = Complex {
+        re: -1.20,
+        im: 0.35,
+    };
+    let lower_right = Complex { re: -1.0, im: 0.20 };
+    // Sizes that do not divide evenly between the threads.
+    for bounds in [(97, 61), (1, 7), (40, 1)] {
+        let mut expected = vec![0; bounds.0 * bounds.1];
+        render(&mut expected, bounds, upper_left, lower_right);
+        for renderer in [Renderer::Serial, Renderer::Bands, Renderer::Rayon] {
+            for threads in [1, 3, 8] {
+                let mut pixels = vec![0; bounds.0 * bounds.1];
+                renderer.render(&mut pixels, bounds, upper_left, lower_right, threads);
+                assert!(
+                    pixels == expected,
+                    "{:?} with {} threads",
+                    renderer,
+                    threads
+                );
+            }
+        }
+    }
+}
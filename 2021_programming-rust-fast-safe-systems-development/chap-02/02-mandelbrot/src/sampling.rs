@@ -0,0 +1,133 @@
+//! Anti-aliasing by supersampling.
+//!
+//! Each pixel is split into a grid of `samples` by `samples` cells, and one
+//! point is taken at random within each cell (jittered sampling). The cells
+//! are coloured one by one, then each pixel gets the average of their
+//! colours, so that only one colour per pixel is kept.
+//! The random offsets come from a hash of the pixel and the cell, so that an
+//! image is the same however it is split into bands.
+
+use num::Complex;
+
+use crate::colour::Rgb;
+use crate::subpixel_to_point;
+
+/// A well-mixed 64-bit hash of `x` (the finalizer of SplitMix64).
+fn mix(x: u64) -> u64 {
+    let mut x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// A number from 0 to 1, random but always the same for the same arguments.
+fn random(pixel: (usize, usize), sample: usize, axis: u64) -> f64 {
+    let hash = mix(mix(mix(pixel.0 as u64) ^ pixel.1 as u64) ^ ((sample as u64) << 1 | axis));
+    // The top 53 bits, as many as an `f64` holds.
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Where sample number `sample` of `pixel` is taken, as an offset from the
+/// upper-left corner of the pixel, in pixels.
+///
+/// With one sample per pixel there is no jitter: the sample is the corner,
+/// the point `pixel_to_point` gives.
+pub fn sample_offset(pixel: (usize, usize), sample: usize, samples: usize) -> (f64, f64) {
+    if samples == 1 {
+        return (0.0, 0.0);
+    }
+    let (column, row) = (sample % samples, sample / samples);
+    (
+        (column as f64 + random(pixel, sample, 0)) / samples as f64,
+        (row as f64 + random(pixel, sample, 1)) / samples as f64,
+    )
+}
+
+#[test]
+fn test_sample_offset() {
+    assert_eq!(sample_offset((3, 4), 0, 1), (0.0, 0.0));
+    for sample in 0..9 {
+        let (x, y) = sample_offset((3, 4), sample, 3);
+        // Each sample lies in its own cell of the pixel...
+        let cell = ((sample % 3) as f64 / 3.0, (sample / 3) as f64 / 3.0);
+        assert!(x >= cell.0 && x < cell.0 + 1.0 / 3.0);
+        assert!(y >= cell.1 && y < cell.1 + 1.0 / 3.0);
+        // ...and is the same every time.
+        assert_eq!(sample_offset((3, 4), sample, 3), (x, y));
+    }
+    assert_ne!(sample_offset((3, 4), 0, 3), sample_offset((4, 3), 0, 3));
+}
+
+/// Colour the pixels of the rows starting at row `top` into `band`, as RGB,
+/// each with the average of the `colour`s of `value` at its `samples *
+/// samples` samples.
+#[allow(clippy::too_many_arguments)]
+pub fn sample_colours(
+    band: &mut [u8],
+    bounds: (usize, usize),
+    top: usize,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    samples: usize,
+    value: &dyn Fn(Complex<f64>) -> Option<f64>,
+    colour: &dyn Fn(Option<f64>) -> Rgb,
+) {
+    let per_pixel = samples * samples;
+    for (i, row) in band.chunks_exact_mut(bounds.0 * 3).enumerate() {
+        for (column, pixel) in row.chunks_exact_mut(3).enumerate() {
+            let corner = (column, top + i);
+            let mut sums = [0_usize; 3];
+            for sample in 0..per_pixel {
+                let (x, y) = sample_offset(corner, sample, samples);
+                let subpixel = (column as f64 + x, (top + i) as f64 + y);
+                let rgb = colour(value(subpixel_to_point(
+                    bounds,
+                    subpixel,
+                    upper_left,
+                    lower_right,
+                )));
+                for (sum, channel) in sums.iter_mut().zip(rgb) {
+                    *sum += channel as usize;
+                }
+            }
+            for (channel, sum) in pixel.iter_mut().zip(sums) {
+                *channel = ((sum + per_pixel / 2) / per_pixel) as u8;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sample_colours() {
+    let upper_left = Complex { re: 0.0, im: 1.0 };
+    let lower_right = Complex { re: 2.0, im: 0.0 };
+    // The left half of the first pixel is in the set.
+    let value = |point: Complex<f64>| (point.re >= 0.5).then_some(1.0);
+    let colour = |value: Option<f64>| match value {
+        None => [0, 0, 0],
+        Some(_) => [255, 255, 255],
+    };
+    let mut band = [0; 6];
+    sample_colours(
+        &mut band,
+        (2, 1),
+        0,
+        upper_left,
+        lower_right,
+        2,
+        &value,
+        &colour,
+    );
+    assert_eq!(band, [128, 128, 128, 255, 255, 255]);
+    sample_colours(
+        &mut band,
+        (2, 1),
+        0,
+        upper_left,
+        lower_right,
+        1,
+        &value,
+        &colour,
+    );
+    assert_eq!(band, [0, 0, 0, 255, 255, 255]);
+}
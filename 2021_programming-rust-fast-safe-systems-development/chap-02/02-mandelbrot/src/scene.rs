@@ -0,0 +1,271 @@
+//! Scenes: every parameter of an image, in a TOML or JSON file, and in the
+//! `tEXt` chunks of the PNG files rendered.
+//!
+//! A scene holds its parameters as they are written on the command line,
+//! and is rendered by turning it back into command-line arguments, so that
+//! it is checked exactly as they are.
+
+use num::Complex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use mandelbrot::deep::DeepView;
+
+use crate::Settings;
+
+/// The prefix of the keywords of the `tEXt` chunks holding a scene.
+const KEYWORD_PREFIX: &str = "mandelbrot:";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    /// The size of the image, as `WIDTHxHEIGHT`.
+    pub size: String,
+    /// The corners of the view, as `RE,IM`, unless it is a deep zoom.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upper_left: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower_right: Option<String>,
+    /// The centre and width of a deep zoom.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(default = "default_fractal")]
+    pub fractal: String,
+    /// A built-in palette, a gradient file, or the text of a gradient.
+    #[serde(default = "default_palette")]
+    pub palette: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+    #[serde(default)]
+    pub smooth: bool,
+    #[serde(default)]
+    pub equalize: bool,
+    #[serde(default = "default_mode")]
+    pub mode: String,
+}
+
+fn default_fractal() -> String {
+    "mandelbrot".to_string()
+}
+
+fn default_palette() -> String {
+    "grayscale".to_string()
+}
+
+fn default_limit() -> usize {
+    255
+}
+
+fn default_samples() -> usize {
+    1
+}
+
+fn default_mode() -> String {
+    "escape".to_string()
+}
+
+impl Scene {
+    /// The scene of an image of size `bounds` between `upper_left` and
+    /// `lower_right`, or of `deep` if given, rendered with `settings`.
+    pub fn new(
+        bounds: (usize, usize),
+        upper_left: Complex<f64>,
+        lower_right: Complex<f64>,
+        deep: Option<&DeepView>,
+        settings: &Settings,
+    ) -> Scene {
+        let complex = |c: Complex<f64>| format!("{},{}", c.re, c.im);
+        let (corners, center) = match deep {
+            Some(view) => (
+                None,
+                Some((format!("{},{}", view.center_re, view.center_im), view.width)),
+            ),
+            None => (Some((complex(upper_left), complex(lower_right))), None),
+        };
+        let (upper_left, lower_right) = corners.unzip();
+        let (center, width) = center.unzip();
+        Scene {
+            size: format!("{}x{}", bounds.0, bounds.1),
+            upper_left,
+            lower_right,
+            center,
+            width,
+            fractal: settings.fractal.to_string(),
+            palette: match settings.palette.name() {
+                Some(name) => name.to_string(),
+                None => settings.palette.to_string(),
+            },
+            limit: settings.limit,
+            samples: settings.samples,
+            smooth: settings.smooth,
+            equalize: settings.equalize,
+            mode: settings.mode.to_string(),
+        }
+    }
+
+    /// Read the scene file `filename`: JSON if its name ends in `.json`, or
+    /// else TOML.
+    pub fn load(filename: &str) -> Result<Scene, String> {
+        let text = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        if filename.ends_with(".json") {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        }
+    }
+
+    /// The scene as `tEXt` chunks, one per parameter, each value written as
+    /// on the command line.
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        let mut metadata = vec![(
+            "Software".to_string(),
+            format!("mandelbrot {}", env!("CARGO_PKG_VERSION")),
+        )];
+        let fields = match serde_json::to_value(self) {
+            Ok(Value::Object(fields)) => fields,
+            _ => unreachable!("a scene is a JSON object"),
+        };
+        for (name, value) in fields {
+            let text = match value {
+                Value::String(text) => text,
+                value => value.to_string(),
+            };
+            metadata.push((format!("{}{}", KEYWORD_PREFIX, name), text));
+        }
+        metadata
+    }
+
+    /// The scene stored in `metadata` by `Scene::metadata`.
+    pub fn from_metadata(metadata: &[(String, String)]) -> Result<Scene, String> {
+        let mut fields = Map::new();
+        for (keyword, text) in metadata {
+            if let Some(name) = keyword.strip_prefix(KEYWORD_PREFIX) {
+                // Numbers and flags are written as JSON; everything else is
+                // a string.
+                let value = match serde_json::from_str(text) {
+                    Ok(value @ (Value::Number(_) | Value::Bool(_))) => value,
+                    _ => Value::String(text.clone()),
+                };
+                fields.insert(name.to_string(), value);
+            }
+        }
+        if fields.is_empty() {
+            return Err("no scene in the image".to_string());
+        }
+        serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())
+    }
+
+    /// The command-line arguments rendering the scene into `filename`.
+    pub fn args(&self, filename: &str) -> Vec<String> {
+        let mut args = vec![
+            "--fractal".to_string(),
+            self.fractal.clone(),
+            "--palette".to_string(),
+            self.palette.clone(),
+            "--limit".to_string(),
+            self.limit.to_string(),
+            "--samples".to_string(),
+            self.samples.to_string(),
+            "--mode".to_string(),
+            self.mode.clone(),
+        ];
+        if self.smooth {
+            args.push("--smooth".to_string());
+        }
+        if self.equalize {
+            args.push("--equalize".to_string());
+        }
+        if let Some(center) = &self.center {
+            args.extend(["--center".to_string(), center.clone()]);
+        }
+        if let Some(width) = self.width {
+            args.extend(["--width".to_string(), width.to_string()]);
+        }
+        args.extend([filename.to_string(), self.size.clone()]);
+        args.extend(self.upper_left.clone());
+        args.extend(self.lower_right.clone());
+        args
+    }
+}
+
+#[test]
+fn test_scene_files() {
+    let scene: Scene = toml::from_str(
+        r#"
+size = "400x300"
+upper_left = "-1.20,0.35"
+lower_right = "-1,0.20"
+palette = "fire"
+samples = 2
+"#,
+    )
+    .unwrap();
+    assert_eq!(scene.fractal, "mandelbrot");
+    assert_eq!((scene.limit, scene.samples), (255, 2));
+    assert_eq!(
+        scene.args("mandel.png"),
+        [
+            "--fractal",
+            "mandelbrot",
+            "--palette",
+            "fire",
+            "--limit",
+            "255",
+            "--samples",
+            "2",
+            "--mode",
+            "escape",
+            "mandel.png",
+            "400x300",
+            "-1.20,0.35",
+            "-1,0.20"
+        ]
+    );
+
+    let json: Scene = serde_json::from_str(
+        r#"{"size": "400x300", "upper_left": "-1.20,0.35", "lower_right": "-1,0.20",
+            "palette": "fire", "samples": 2}"#,
+    )
+    .unwrap();
+    assert_eq!(json, scene);
+    assert!(toml::from_str::<Scene>("size = \"1x1\"\ncolour = \"red\"").is_err());
+}
+
+#[test]
+fn test_scene_metadata() {
+    use crate::parse_args;
+
+    let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+    for command in [
+        args(&[
+            "--fractal",
+            "julia:-0.8,0.156",
+            "--palette",
+            "ocean",
+            "--smooth",
+            "mandel.png",
+            "400x300",
+            "-1.5,1",
+            "1.5,-1",
+        ]),
+        args(&["--center", "0,1", "--width", "1e-30", "mandel.png", "64x48"]),
+    ] {
+        let parsed = parse_args(&command).unwrap();
+        let scene = Scene::new(
+            parsed.bounds,
+            parsed.upper_left,
+            parsed.lower_right,
+            parsed.deep.as_ref(),
+            &parsed.settings,
+        );
+        let metadata = scene.metadata();
+        assert!(metadata.contains(&("mandelbrot:limit".to_string(), "255".to_string())));
+        assert_eq!(Scene::from_metadata(&metadata).as_ref(), Ok(&scene));
+        assert_eq!(parse_args(&scene.args("mandel.png")), Ok(parsed));
+    }
+    assert!(Scene::from_metadata(&[("Software".to_string(), "gimp".to_string())]).is_err());
+}
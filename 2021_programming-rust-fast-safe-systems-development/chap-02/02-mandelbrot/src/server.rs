@@ -0,0 +1,277 @@
+//! The `serve` command: PNG tiles of the plane over HTTP, and a page to
+//! explore them with.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use actix_web::web::{self, Bytes};
+use actix_web::{App, HttpResponse, HttpServer};
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use mandelbrot::colour::{Palette, PALETTES};
+use mandelbrot::parallel::Renderer;
+use mandelbrot::tiles::{LruCache, Tile, MAX_ZOOM, TILE_SIZE};
+use mandelbrot::{encode_png, ColorType};
+
+use crate::{ServeArgs, Settings};
+
+/// The highest iteration limit a request may ask for, so that one tile
+/// cannot keep a worker busy for minutes.
+const MAX_LIMIT: usize = 100_000;
+
+const VIEWER: &str = include_str!("viewer.html");
+
+/// What the server shares between its workers.
+struct TileServer {
+    /// How tiles are rendered when the query does not say otherwise.
+    settings: Settings,
+    cache: Mutex<LruCache<TileKey, Bytes>>,
+    /// The tiles being rendered, which requests for the same tile wait for
+    /// instead of rendering it again. `None` if the rendering failed.
+    in_flight: Mutex<HashMap<TileKey, Arc<OnceCell<Option<Bytes>>>>>,
+}
+
+impl TileServer {
+    /// A server rendering tiles with `settings`, keeping the last `cache`
+    /// of them.
+    ///
+    /// Each tile is rendered on a single thread: tiles are small, and the
+    /// requests for the tiles of a view already come in together.
+    fn new(settings: Settings, cache: usize) -> TileServer {
+        TileServer {
+            settings: Settings {
+                renderer: Renderer::Serial,
+                ..settings
+            },
+            cache: Mutex::new(LruCache::new(cache)),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The PNG image of the tile of `key`, rendered with `settings` unless
+    /// it is cached or already being rendered.
+    async fn png(&self, key: TileKey, settings: Settings) -> Option<Bytes> {
+        if let Some(png) = self.cache.lock().unwrap().get(&key) {
+            return Some(png);
+        }
+        let rendering = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let tile = key.tile;
+        let png = rendering
+            .get_or_init(|| async move {
+                let rendered = web::block(move || {
+                    let (upper_left, lower_right) = tile.corners();
+                    let bounds = (TILE_SIZE, TILE_SIZE);
+                    let pixels = settings.render(bounds, upper_left, lower_right, None);
+                    encode_png(&pixels, bounds, ColorType::RGB(8))
+                })
+                .await;
+                match rendered {
+                    Ok(Ok(png)) => Some(Bytes::from(png)),
+                    _ => None,
+                }
+            })
+            .await
+            .clone();
+
+        // The first request done with the tile caches it before it stops
+        // being in flight, so that no request renders it again meanwhile.
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|entry| Arc::ptr_eq(entry, &rendering))
+        {
+            if let Some(png) = &png {
+                self.cache.lock().unwrap().insert(key.clone(), png.clone());
+            }
+            in_flight.remove(&key);
+        }
+        png
+    }
+}
+
+/// A tile, and the query parameters it was rendered with.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TileKey {
+    tile: Tile,
+    limit: usize,
+    palette: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TileQuery {
+    limit: Option<usize>,
+    /// One of the built-in palettes: a request may not read files.
+    palette: Option<String>,
+}
+
+/// Serve tiles on the address and port of `args` until interrupted.
+pub fn serve(args: ServeArgs) -> std::io::Result<()> {
+    let server = web::Data::new(TileServer::new(args.settings, args.cache));
+    actix_web::rt::System::new().block_on(async move {
+        let http = HttpServer::new(move || App::new().configure(configure(server.clone())))
+            .bind((args.address.as_str(), args.port))?;
+        println!("Serving on http://{}:{}...", args.address, args.port);
+        http.run().await
+    })
+}
+
+/// Register the routes of the tile server, sharing `server`.
+fn configure(server: web::Data<TileServer>) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |cfg| {
+        cfg.app_data(server)
+            .route("/", web::get().to(get_index))
+            .route("/tiles/{z}/{x}/{y}.png", web::get().to(get_tile));
+    }
+}
+
+async fn get_index(server: web::Data<TileServer>) -> HttpResponse {
+    let palettes: String = PALETTES
+        .iter()
+        .map(|name| format!(r#"<option value="{0}">{0}</option>"#, name))
+        .collect();
+    HttpResponse::Ok().content_type("text/html").body(
+        VIEWER
+            .replace("{palettes}", &palettes)
+            .replace("{limit}", &server.settings.limit.to_string())
+            .replace("{max_limit}", &MAX_LIMIT.to_string())
+            .replace("{max_zoom}", &MAX_ZOOM.to_string()),
+    )
+}
+
+async fn get_tile(
+    server: web::Data<TileServer>,
+    path: web::Path<(u32, u64, u64)>,
+    query: web::Query<TileQuery>,
+) -> HttpResponse {
+    let (z, x, y) = path.into_inner();
+    let tile = match Tile::new(z, x, y) {
+        Some(tile) => tile,
+        None => return HttpResponse::NotFound().body("no such tile\n"),
+    };
+    let mut settings = server.settings.clone();
+    if let Some(limit) = query.limit {
+        if limit == 0 || limit > MAX_LIMIT {
+            return HttpResponse::BadRequest().body(format!(
+                "the iteration limit must be from 1 to {}\n",
+                MAX_LIMIT
+            ));
+        }
+        settings.limit = limit;
+    }
+    if let Some(name) = &query.palette {
+        settings.palette = match Palette::named(name) {
+            Some(palette) => palette,
+            None => {
+                return HttpResponse::BadRequest().body(format!(
+                    "unknown palette `{}`: expected one of {}\n",
+                    name,
+                    PALETTES.join(", ")
+                ))
+            }
+        };
+    }
+
+    let key = TileKey {
+        tile,
+        limit: settings.limit,
+        palette: query.into_inner().palette,
+    };
+    let png = match server.png(key, settings).await {
+        Some(png) => png,
+        None => return HttpResponse::InternalServerError().body("cannot render tile\n"),
+    };
+    HttpResponse::Ok()
+        .content_type("image/png")
+        .insert_header(("Cache-Control", "public, max-age=86400"))
+        .body(png)
+}
+
+#[cfg(test)]
+fn test_server() -> web::Data<TileServer> {
+    web::Data::new(TileServer::new(Settings::default(), 4))
+}
+
+#[actix_web::test]
+async fn test_concurrent_requests_share_a_rendering() {
+    let server = test_server();
+    let key = TileKey {
+        tile: Tile::new(2, 1, 1).unwrap(),
+        limit: 255,
+        palette: None,
+    };
+    let request = || {
+        let (server, key) = (server.clone(), key.clone());
+        actix_web::rt::spawn(async move { server.png(key, server.settings.clone()).await })
+    };
+    let (first, second) = (request(), request());
+    let first = first.await.unwrap().unwrap();
+    let second = second.await.unwrap().unwrap();
+    // Rendered once: both are the same buffer.
+    assert_eq!(first.as_ptr(), second.as_ptr());
+    assert_eq!(server.cache.lock().unwrap().len(), 1);
+    assert!(server.in_flight.lock().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn test_get_index() {
+    use actix_web::test;
+
+    let app = test::init_service(App::new().configure(configure(test_server()))).await;
+    let response = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    assert!(response.status().is_success());
+    let body = test::read_body(response).await;
+    let page = std::str::from_utf8(&body).unwrap();
+    assert!(page.contains(r#"<option value="fire">fire</option>"#));
+    assert!(page.contains(r#"value="255""#));
+}
+
+#[actix_web::test]
+async fn test_get_tile() {
+    use actix_web::http::StatusCode;
+    use actix_web::test;
+
+    let server = test_server();
+    let app = test::init_service(App::new().configure(configure(server.clone()))).await;
+    let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+    let response = test::call_service(&app, get("/tiles/1/0/1.png?palette=fire")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "image/png");
+    let png = test::read_body(response).await;
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(server.cache.lock().unwrap().len(), 1);
+
+    // The same tile comes from the cache; another limit is another tile.
+    let again =
+        test::read_body(test::call_service(&app, get("/tiles/1/0/1.png?palette=fire")).await).await;
+    assert_eq!(again, png);
+    assert_eq!(server.cache.lock().unwrap().len(), 1);
+    let response = test::call_service(&app, get("/tiles/1/0/1.png?palette=fire&limit=50")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(server.cache.lock().unwrap().len(), 2);
+
+    for (uri, status) in [
+        ("/tiles/1/2/0.png", StatusCode::NOT_FOUND),
+        ("/tiles/99/0/0.png", StatusCode::NOT_FOUND),
+        ("/tiles/0/0/0.png?limit=0", StatusCode::BAD_REQUEST),
+        ("/tiles/0/0/0.png?limit=many", StatusCode::BAD_REQUEST),
+        (
+            "/tiles/0/0/0.png?palette=/etc/passwd",
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        assert_eq!(
+            test::call_service(&app, get(uri)).await.status(),
+            status,
+            "{}",
+            uri
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Square tiles of the plane, as web maps lay them out, and a cache for them.
+//!
+//! At zoom level `z` the plane is cut into `2^z` by `2^z` tiles of
+//! `TILE_SIZE` pixels, numbered from the upper left. Level 0 is a single
+//! tile showing the whole Mandelbrot set.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use num::Complex;
+
+/// The width and height of a tile, in pixels.
+pub const TILE_SIZE: usize = 256;
+
+/// The deepest zoom level: past it, neighbouring pixels are too close for
+/// `f64` to tell apart.
+pub const MAX_ZOOM: u32 = 40;
+
+/// The upper-left corner of tile 0 at level 0, and its width.
+const WORLD_UPPER_LEFT: Complex<f64> = Complex { re: -2.75, im: 2.0 };
+const WORLD_WIDTH: f64 = 4.0;
+
+/// The tile in column `x` and row `y` of zoom level `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    pub z: u32,
+    pub x: u64,
+    pub y: u64,
+}
+
+impl Tile {
+    /// The tile at `z`, `x`, `y`, or `None` if there is no such tile.
+    pub fn new(z: u32, x: u64, y: u64) -> Option<Tile> {
+        if z > MAX_ZOOM || x >> z != 0 || y >> z != 0 {
+            return None;
+        }
+        Some(Tile { z, x, y })
+    }
+
+    /// The upper-left and lower-right corners of the tile.
+    pub fn corners(&self) -> (Complex<f64>, Complex<f64>) {
+        let width = WORLD_WIDTH / (1_u64 << self.z) as f64;
+        let upper_left = Complex {
+            re: WORLD_UPPER_LEFT.re + self.x as f64 * width,
+            im: WORLD_UPPER_LEFT.im - self.y as f64 * width,
+        };
+        let lower_right = Complex {
+            re: upper_left.re + width,
+            im: upper_left.im - width,
+        };
+        (upper_left, lower_right)
+    }
+}
+
+#[test]
+fn test_tile() {
+    assert_eq!(
+        Tile::new(0, 0, 0).unwrap().corners(),
+        (
+            Complex { re: -2.75, im: 2.0 },
+            Complex { re: 1.25, im: -2.0 }
+        )
+    );
+    assert_eq!(
+        Tile::new(2, 3, 1).unwrap().corners(),
+        (Complex { re: 0.25, im: 1.0 }, Complex { re: 1.25, im: 0.0 })
+    );
+    assert_eq!(Tile::new(2, 4, 0), None);
+    assert_eq!(Tile::new(0, 0, 1), None);
+    assert_eq!(Tile::new(MAX_ZOOM + 1, 0, 0), None);
+}
+
+/// A map holding at most `capacity` entries, forgetting the least recently
+/// used one to make room for another.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    /// The entries, with the time each was last used.
+    entries: HashMap<K, (V, u64)>,
+    /// The keys of `entries` by the time they were last used.
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> LruCache<K, V> {
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        assert!(capacity > 0);
+        LruCache {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The value of `key`, which becomes the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.recency.remove(used);
+        *used = self.clock;
+        self.recency.insert(self.clock, key.clone());
+        Some(value.clone())
+    }
+
+    /// Set the value of `key`, evicting the least recently used entry if
+    /// the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.clock += 1;
+        if let Some((_, used)) = self.entries.remove(&key) {
+            self.recency.remove(&used);
+        } else if self.entries.len() == self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, self.clock));
+    }
+}
+
+#[test]
+fn test_lru_cache() {
+    let mut cache = LruCache::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+    assert_eq!(cache.get(&"a"), Some(1));
+    // "b" is now the least recently used.
+    cache.insert("c", 3);
+    assert_eq!(cache.get(&"b"), None);
+    assert_eq!(cache.get(&"a"), Some(1));
+    assert_eq!(cache.get(&"c"), Some(3));
+
+    cache.insert("a", 10);
+    cache.insert("d", 4);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"c"), None);
+    assert_eq!(cache.get(&"a"), Some(10));
+}
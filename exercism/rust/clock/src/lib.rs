@@ -1,45 +1,287 @@
-use std::cmp::PartialEq;
+use std::error::Error;
 use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
-pub struct Clock(i32, i32);
+#[cfg(feature = "zoned")]
+pub mod zoned;
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+/// A time of day, to the second, without a date.
+///
+/// Clocks are ordered from midnight to 23:59:59.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Clock(i32);
 
 impl Clock {
     pub fn new(hours: i32, minutes: i32) -> Self {
-        let hours_from_minutes = minutes.div_euclid(60);
-        let normalised_minutes = minutes.rem_euclid(60);
-        let normalised_hours = (hours + hours_from_minutes).rem_euclid(24);
-        Clock {
-            0: normalised_hours,
-            1: normalised_minutes,
-        }
+        Clock::with_seconds(hours, minutes, 0)
+    }
+
+    /// Like `new`, to the second.
+    pub fn with_seconds(hours: i32, minutes: i32, seconds: i32) -> Self {
+        Clock::from_seconds(
+            hours as i64 * SECONDS_PER_HOUR + minutes as i64 * SECONDS_PER_MINUTE + seconds as i64,
+        )
+    }
+
+    /// The clock `seconds` after midnight, wrapping around as many days as
+    /// needed.
+    fn from_seconds(seconds: i64) -> Self {
+        Clock(seconds.rem_euclid(SECONDS_PER_DAY) as i32)
+    }
+
+    pub fn hours(&self) -> i32 {
+        self.0 / SECONDS_PER_HOUR as i32
+    }
+
+    pub fn minutes(&self) -> i32 {
+        self.0 / SECONDS_PER_MINUTE as i32 % 60
+    }
+
+    pub fn seconds(&self) -> i32 {
+        self.0 % SECONDS_PER_MINUTE as i32
     }
 
     pub fn add_minutes(&self, minutes: i32) -> Self {
-        Clock::new(self.0, self.1 + minutes)
+        *self + Duration::minutes(minutes as i64)
+    }
+
+    /// The time on a 12-hour clock, like `9:05am` or `12:30:15pm`.
+    pub fn to_12_hour_string(&self) -> String {
+        let (hours, suffix) = match self.hours() {
+            0 => (12, "am"),
+            hours @ 1..=11 => (hours, "am"),
+            12 => (12, "pm"),
+            hours => (hours - 12, "pm"),
+        };
+        match self.seconds() {
+            0 => format!("{}:{:02}{}", hours, self.minutes(), suffix),
+            seconds => format!("{}:{:02}:{:02}{}", hours, self.minutes(), seconds, suffix),
+        }
     }
 }
 
 impl fmt::Display for Clock {
+    /// `HH:MM`, or `HH:MM:SS` when the seconds are not zero.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:0>2}:{:0>2}", self.0, self.1)
+        write!(f, "{:0>2}:{:0>2}", self.hours(), self.minutes())?;
+        if self.seconds() != 0 {
+            write!(f, ":{:0>2}", self.seconds())?;
+        }
+        Ok(())
     }
 }
 
-impl PartialEq for Clock {
-    fn eq(&self, other: &Clock) -> bool {
-        self.0 == other.0 && self.1 == other.1
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock")
+            .field("hours", &self.hours())
+            .field("minutes", &self.minutes())
+            .field("seconds", &self.seconds())
+            .finish()
     }
+}
+
+/// Why a clock could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseClockError {
+    /// Not of the form `HH:MM[:SS]` or `H:MM[:SS]am`.
+    Format,
+    /// Hours, minutes or seconds past the end of their range, like `24:00`
+    /// or `13:00pm`.
+    OutOfRange,
+}
 
-    fn ne(&self, other: &Clock) -> bool {
-        !self.eq(other)
+impl fmt::Display for ParseClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseClockError::Format => write!(f, "expected a time like 09:30, 9:30am or 09:30:15"),
+            ParseClockError::OutOfRange => write!(f, "time out of range"),
+        }
     }
 }
 
-impl fmt::Debug for Clock {
+impl Error for ParseClockError {}
+
+impl FromStr for Clock {
+    type Err = ParseClockError;
+
+    /// Parse a 24-hour time, `HH:MM` or `HH:MM:SS`, or a 12-hour one, with
+    /// `am` or `pm` after it, like `9:30am` or `12:00 PM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        let (time, pm) = match (lower.strip_suffix("am"), lower.strip_suffix("pm")) {
+            (Some(time), _) => (time.trim_end(), Some(false)),
+            (_, Some(time)) => (time.trim_end(), Some(true)),
+            _ => (s, None),
+        };
+
+        let mut fields = time.split(':').map(|field| {
+            if field.is_empty() || field.len() > 2 || !field.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseClockError::Format);
+            }
+            field.parse::<i32>().map_err(|_| ParseClockError::Format)
+        });
+        let (hours, minutes, seconds) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(hours), Some(minutes), seconds) => (hours?, minutes?, seconds.unwrap_or(Ok(0))?),
+            _ => return Err(ParseClockError::Format),
+        };
+        if fields.next().is_some() {
+            return Err(ParseClockError::Format);
+        }
+
+        let hours = match pm {
+            None if hours < 24 => hours,
+            Some(pm) if (1..=12).contains(&hours) => hours % 12 + if pm { 12 } else { 0 },
+            _ => return Err(ParseClockError::OutOfRange),
+        };
+        if minutes >= 60 || seconds >= 60 {
+            return Err(ParseClockError::OutOfRange);
+        }
+        Ok(Clock::with_seconds(hours, minutes, seconds))
+    }
+}
+
+/// A signed length of time, to the second, to move clocks by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration(i64);
+
+impl Duration {
+    pub fn hours(hours: i64) -> Self {
+        Duration(hours * SECONDS_PER_HOUR)
+    }
+
+    pub fn minutes(minutes: i64) -> Self {
+        Duration(minutes * SECONDS_PER_MINUTE)
+    }
+
+    pub fn seconds(seconds: i64) -> Self {
+        Duration(seconds)
+    }
+
+    /// The length in whole minutes, rounded towards zero.
+    pub fn as_minutes(&self) -> i64 {
+        self.0 / SECONDS_PER_MINUTE
+    }
+
+    pub fn as_seconds(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Duration {
+    /// Like `1h30m`, `-45m` or `2m5s`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Clock")
-            .field("hours", &self.0)
-            .field("minutes", &self.1)
-            .finish()
+        if self.0 < 0 {
+            write!(f, "-")?;
+        }
+        let seconds = self.0.unsigned_abs();
+        let (hours, minutes, seconds) = (
+            seconds / SECONDS_PER_HOUR as u64,
+            seconds / SECONDS_PER_MINUTE as u64 % 60,
+            seconds % SECONDS_PER_MINUTE as u64,
+        );
+        if hours != 0 {
+            write!(f, "{}h", hours)?;
+        }
+        if minutes != 0 || (hours == 0 && seconds == 0) {
+            write!(f, "{}m", minutes)?;
+        }
+        if seconds != 0 {
+            write!(f, "{}s", seconds)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    /// The standard duration, to the second, rounded down.
+    fn from(duration: std::time::Duration) -> Self {
+        Duration(duration.as_secs() as i64)
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+
+    fn neg(self) -> Duration {
+        Duration(-self.0)
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
+
+impl Add<Duration> for Clock {
+    type Output = Clock;
+
+    fn add(self, duration: Duration) -> Clock {
+        Clock::from_seconds(self.0 as i64 + duration.0 % SECONDS_PER_DAY)
+    }
+}
+
+impl Sub<Duration> for Clock {
+    type Output = Clock;
+
+    fn sub(self, duration: Duration) -> Clock {
+        self + -duration
+    }
+}
+
+impl AddAssign<Duration> for Clock {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl SubAssign<Duration> for Clock {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl Sub for Clock {
+    type Output = Duration;
+
+    /// How long after `other` the clock next shows `self`: from zero to
+    /// just under a day, so that the 00:10 bus leaves 20 minutes after
+    /// 23:50.
+    fn sub(self, other: Clock) -> Duration {
+        Duration((self.0 as i64 - other.0 as i64).rem_euclid(SECONDS_PER_DAY))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Clock {
+    /// As its `Display` string, like `"09:30"`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Clock {
+    /// From any string `from_str` parses.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
@@ -0,0 +1,223 @@
+//! Clocks on a date in a time zone, from the IANA database bundled by
+//! `chrono-tz`.
+//!
+//! A plain `Clock` wraps around midnight and knows nothing of daylight
+//! saving time. A `ZonedClock` is an instant: the wall-clock time, the date
+//! and the time zone together, so that it can be converted to other zones
+//! and moved by exact durations across DST transitions.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Sub};
+
+use chrono::{
+    DateTime, Duration as ChronoDuration, LocalResult, NaiveDateTime, Offset, TimeZone, Timelike,
+};
+
+use crate::{Clock, Duration};
+
+pub use chrono::NaiveDate;
+pub use chrono_tz::Tz;
+
+/// Which instant to take when a wall-clock time happens twice, or never, in
+/// a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disambiguation {
+    /// In an overlap, the first time the clock shows the time. In a gap, the
+    /// time read with the offset from after the gap, which lands before it:
+    /// 02:30 on the night clocks go from 02:00 to 03:00 is 01:30.
+    Earlier,
+    /// In an overlap, the second time the clock shows the time. In a gap,
+    /// the time read with the offset from before the gap, which lands after
+    /// it: 02:30 becomes 03:30.
+    Later,
+}
+
+/// Why a wall-clock time does not name exactly one instant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalTimeError {
+    /// The clocks skip over the time, when they go forward.
+    Nonexistent,
+    /// The clocks show the time twice, when they go back.
+    Ambiguous {
+        earlier: ZonedClock,
+        later: ZonedClock,
+    },
+}
+
+impl fmt::Display for LocalTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalTimeError::Nonexistent => write!(f, "the clocks skip over this time"),
+            LocalTimeError::Ambiguous { earlier, later } => {
+                write!(f, "the time is ambiguous: {} or {}", earlier, later)
+            }
+        }
+    }
+}
+
+impl Error for LocalTimeError {}
+
+/// A clock time on a date in a time zone: one instant.
+///
+/// Zoned clocks are equal, ordered and hashed by instant, whatever their
+/// zones.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ZonedClock(DateTime<Tz>);
+
+impl ZonedClock {
+    /// The instant the clocks in `tz` show `clock` on `date`, if there is
+    /// exactly one.
+    pub fn new(date: NaiveDate, clock: Clock, tz: Tz) -> Result<Self, LocalTimeError> {
+        match tz.from_local_datetime(&local(date, clock)) {
+            LocalResult::Single(instant) => Ok(ZonedClock(instant)),
+            LocalResult::Ambiguous(earlier, later) => Err(LocalTimeError::Ambiguous {
+                earlier: ZonedClock(earlier),
+                later: ZonedClock(later),
+            }),
+            LocalResult::None => Err(LocalTimeError::Nonexistent),
+        }
+    }
+
+    /// Like `new`, picking an instant by `disambiguation` in DST gaps and
+    /// overlaps.
+    pub fn resolve(date: NaiveDate, clock: Clock, tz: Tz, disambiguation: Disambiguation) -> Self {
+        match ZonedClock::new(date, clock, tz) {
+            Ok(zoned) => zoned,
+            Err(LocalTimeError::Ambiguous { earlier, later }) => match disambiguation {
+                Disambiguation::Earlier => earlier,
+                Disambiguation::Later => later,
+            },
+            Err(LocalTimeError::Nonexistent) => {
+                // The clocks show times before `local` until the transition
+                // and after it from then on, so bisect for the last second
+                // before the transition and the first after it.
+                let local = local(date, clock);
+                let shown = |utc: NaiveDateTime| tz.from_utc_datetime(&utc).naive_local();
+                let mut before = local - ChronoDuration::days(1);
+                let mut after = local + ChronoDuration::days(1);
+                while after - before > ChronoDuration::seconds(1) {
+                    let middle = before + (after - before) / 2;
+                    if shown(middle) < local {
+                        before = middle;
+                    } else {
+                        after = middle;
+                    }
+                }
+                let offset_at =
+                    |utc: NaiveDateTime| tz.offset_from_utc_datetime(&utc).fix().local_minus_utc();
+                let offset = match disambiguation {
+                    Disambiguation::Earlier => offset_at(after),
+                    Disambiguation::Later => offset_at(before),
+                };
+                let utc = local - ChronoDuration::seconds(offset as i64);
+                ZonedClock(tz.from_utc_datetime(&utc))
+            }
+        }
+    }
+
+    /// The instant `seconds` after the start of 1970 in UTC, in `tz`.
+    pub fn from_unix_seconds(seconds: i64, tz: Tz) -> Option<Self> {
+        DateTime::from_timestamp(seconds, 0).map(|utc| ZonedClock(utc.with_timezone(&tz)))
+    }
+
+    pub fn unix_seconds(&self) -> i64 {
+        self.0.timestamp()
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.0.date_naive()
+    }
+
+    pub fn clock(&self) -> Clock {
+        let time = self.0.time();
+        Clock::with_seconds(
+            time.hour() as i32,
+            time.minute() as i32,
+            time.second() as i32,
+        )
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.0.timezone()
+    }
+
+    /// How far the zone's clocks are ahead of UTC at this instant.
+    pub fn utc_offset(&self) -> Duration {
+        Duration::seconds(self.0.offset().fix().local_minus_utc() as i64)
+    }
+
+    /// Whether the zone is on daylight saving time at this instant.
+    pub fn is_dst(&self) -> bool {
+        use chrono_tz::OffsetComponents;
+        !self.0.offset().dst_offset().is_zero()
+    }
+
+    /// The same instant on the clocks of `tz`.
+    pub fn with_tz(&self, tz: Tz) -> Self {
+        ZonedClock(self.0.with_timezone(&tz))
+    }
+}
+
+/// The wall-clock time `clock` on `date`.
+fn local(date: NaiveDate, clock: Clock) -> NaiveDateTime {
+    date.and_hms_opt(
+        clock.hours() as u32,
+        clock.minutes() as u32,
+        clock.seconds() as u32,
+    )
+    .expect("a clock is a valid time of day")
+}
+
+impl fmt::Display for ZonedClock {
+    /// Like `2024-03-10 03:30 -04:00 America/New_York`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.date(),
+            self.clock(),
+            self.0.format("%:z"),
+            self.tz()
+        )
+    }
+}
+
+impl fmt::Debug for ZonedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZonedClock")
+            .field("date", &self.date())
+            .field("clock", &self.clock())
+            .field("tz", &self.tz())
+            .field("utc_offset", &self.utc_offset())
+            .finish()
+    }
+}
+
+impl Add<Duration> for ZonedClock {
+    type Output = ZonedClock;
+
+    /// The instant `duration` later: 24 hours after noon the day before the
+    /// clocks go forward is 13:00.
+    fn add(self, duration: Duration) -> ZonedClock {
+        ZonedClock(self.0 + ChronoDuration::seconds(duration.as_seconds()))
+    }
+}
+
+impl Sub<Duration> for ZonedClock {
+    type Output = ZonedClock;
+
+    fn sub(self, duration: Duration) -> ZonedClock {
+        self + -duration
+    }
+}
+
+impl Sub for ZonedClock {
+    type Output = Duration;
+
+    /// The time elapsed from `other` to `self`, negative if `self` is
+    /// earlier.
+    fn sub(self, other: ZonedClock) -> Duration {
+        Duration::seconds(self.unix_seconds() - other.unix_seconds())
+    }
+}
@@ -1,4 +1,4 @@
-use clock::Clock;
+use clock::{Clock, Duration, ParseClockError};
 
 //
 // Clock Creation
@@ -297,3 +297,207 @@ fn test_compare_clocks_for_equality() {
 // fn test_compare_full_clock_and_zeroed_clock() {
 //     assert_eq!(Clock::new(24, 0), Clock::new(0, 0));
 // }
+
+//
+// Seconds
+//
+
+#[test]
+fn test_clock_with_seconds() {
+    let clock = Clock::with_seconds(8, 5, 30);
+    assert_eq!(clock.to_string(), "08:05:30");
+    assert_eq!(
+        (clock.hours(), clock.minutes(), clock.seconds()),
+        (8, 5, 30)
+    );
+}
+
+#[test]
+fn test_clock_with_negative_seconds_rolls_back() {
+    assert_eq!(Clock::with_seconds(0, 0, -1).to_string(), "23:59:59");
+}
+
+#[test]
+fn test_clock_with_zero_seconds_is_a_minute_clock() {
+    assert_eq!(Clock::with_seconds(10, 0, 0), Clock::new(10, 0));
+}
+
+//
+// 12-Hour Format
+//
+
+#[test]
+fn test_12_hour_format() {
+    assert_eq!(Clock::new(0, 0).to_12_hour_string(), "12:00am");
+    assert_eq!(Clock::new(9, 5).to_12_hour_string(), "9:05am");
+    assert_eq!(Clock::new(12, 30).to_12_hour_string(), "12:30pm");
+    assert_eq!(
+        Clock::with_seconds(23, 59, 1).to_12_hour_string(),
+        "11:59:01pm"
+    );
+}
+
+//
+// Parsing
+//
+
+#[test]
+fn test_parse_24_hour_clocks() {
+    assert_eq!("09:30".parse(), Ok(Clock::new(9, 30)));
+    assert_eq!("9:30".parse(), Ok(Clock::new(9, 30)));
+    assert_eq!("23:59:59".parse(), Ok(Clock::with_seconds(23, 59, 59)));
+}
+
+#[test]
+fn test_parse_12_hour_clocks() {
+    assert_eq!("9:30am".parse(), Ok(Clock::new(9, 30)));
+    assert_eq!("12:00am".parse(), Ok(Clock::new(0, 0)));
+    assert_eq!("12:15 PM".parse(), Ok(Clock::new(12, 15)));
+    assert_eq!("11:59:59pm".parse(), Ok(Clock::with_seconds(23, 59, 59)));
+}
+
+#[test]
+fn test_parse_round_trips() {
+    for clock in [
+        Clock::new(0, 0),
+        Clock::new(7, 45),
+        Clock::with_seconds(18, 0, 9),
+    ] {
+        assert_eq!(clock.to_string().parse(), Ok(clock));
+        assert_eq!(clock.to_12_hour_string().parse(), Ok(clock));
+    }
+}
+
+#[test]
+fn test_parse_rejects_bad_formats() {
+    for s in [
+        "",
+        "9",
+        "9:",
+        ":30",
+        "09:30:",
+        "09:30:00:00",
+        "9h30",
+        "009:30",
+        "+9:30",
+        "9:30xm",
+    ] {
+        assert_eq!(s.parse::<Clock>(), Err(ParseClockError::Format), "{:?}", s);
+    }
+}
+
+#[test]
+fn test_parse_rejects_out_of_range_times() {
+    for s in ["24:00", "12:60", "12:00:60", "0:30am", "13:00pm"] {
+        assert_eq!(
+            s.parse::<Clock>(),
+            Err(ParseClockError::OutOfRange),
+            "{:?}",
+            s
+        );
+    }
+}
+
+//
+// Durations
+//
+
+#[test]
+fn test_add_duration() {
+    assert_eq!(
+        Clock::new(23, 50) + Duration::minutes(20),
+        Clock::new(0, 10)
+    );
+    assert_eq!(Clock::new(10, 0) + Duration::hours(-25), Clock::new(9, 0));
+    assert_eq!(
+        Clock::new(10, 0) + Duration::hours(24 * 365),
+        Clock::new(10, 0)
+    );
+}
+
+#[test]
+fn test_subtract_duration() {
+    let mut clock = Clock::new(0, 10);
+    clock -= Duration::minutes(20);
+    assert_eq!(clock, Clock::new(23, 50));
+    clock += Duration::seconds(5);
+    assert_eq!(clock, Clock::with_seconds(23, 50, 5));
+}
+
+#[test]
+fn test_difference_between_clocks() {
+    assert_eq!(
+        Clock::new(0, 10) - Clock::new(23, 50),
+        Duration::minutes(20)
+    );
+    assert_eq!(
+        Clock::new(23, 50) - Clock::new(0, 10),
+        Duration::minutes(23 * 60 + 40)
+    );
+    assert_eq!(Clock::new(9, 0) - Clock::new(9, 0), Duration::seconds(0));
+}
+
+#[test]
+fn test_duration_arithmetic_and_display() {
+    let d = Duration::hours(1) + Duration::minutes(30);
+    assert_eq!(d.as_minutes(), 90);
+    assert_eq!(d.to_string(), "1h30m");
+    assert_eq!((-Duration::minutes(45)).to_string(), "-45m");
+    assert_eq!(
+        (Duration::minutes(2) + Duration::seconds(5)).to_string(),
+        "2m5s"
+    );
+    assert_eq!(Duration::default().to_string(), "0m");
+    assert_eq!(
+        Duration::from(std::time::Duration::from_millis(90_500)),
+        Duration::seconds(90)
+    );
+}
+
+//
+// Ordering and Hashing
+//
+
+#[test]
+fn test_clocks_are_ordered_from_midnight() {
+    let mut timetable = vec![
+        Clock::new(17, 5),
+        Clock::new(0, 15),
+        Clock::with_seconds(9, 0, 1),
+        Clock::new(9, 0),
+    ];
+    timetable.sort();
+    assert_eq!(
+        timetable,
+        [
+            Clock::new(0, 15),
+            Clock::new(9, 0),
+            Clock::with_seconds(9, 0, 1),
+            Clock::new(17, 5)
+        ]
+    );
+}
+
+#[test]
+fn test_equal_clocks_hash_alike() {
+    let set: std::collections::HashSet<Clock> = [
+        Clock::new(10, 37),
+        Clock::new(34, 37),
+        Clock::new(10, -1403),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_as_string() {
+    let clock = Clock::new(7, 5);
+    assert_eq!(serde_json::to_string(&clock).unwrap(), "\"07:05\"");
+    assert_eq!(
+        serde_json::from_str::<Clock>("\"7:05pm\"").unwrap(),
+        Clock::new(19, 5)
+    );
+    assert!(serde_json::from_str::<Clock>("\"25:00\"").is_err());
+}
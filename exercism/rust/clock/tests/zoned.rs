@@ -0,0 +1,219 @@
+#![cfg(feature = "zoned")]
+
+use clock::zoned::{Disambiguation, LocalTimeError, NaiveDate, Tz, ZonedClock};
+use clock::{Clock, Duration};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+//
+// Creation
+//
+
+#[test]
+fn test_zoned_clock_in_winter_and_summer() {
+    let winter =
+        ZonedClock::new(date(2024, 1, 15), Clock::new(9, 30), Tz::America__New_York).unwrap();
+    assert_eq!(
+        winter.to_string(),
+        "2024-01-15 09:30 -05:00 America/New_York"
+    );
+    assert_eq!(winter.utc_offset(), Duration::hours(-5));
+    assert!(!winter.is_dst());
+
+    let summer =
+        ZonedClock::new(date(2024, 7, 15), Clock::new(9, 30), Tz::America__New_York).unwrap();
+    assert_eq!(summer.utc_offset(), Duration::hours(-4));
+    assert!(summer.is_dst());
+}
+
+#[test]
+fn test_zoned_clock_parts() {
+    let zoned = ZonedClock::new(
+        date(2024, 2, 29),
+        Clock::with_seconds(23, 59, 59),
+        Tz::Asia__Kolkata,
+    )
+    .unwrap();
+    assert_eq!(zoned.date(), date(2024, 2, 29));
+    assert_eq!(zoned.clock(), Clock::with_seconds(23, 59, 59));
+    assert_eq!(zoned.tz(), Tz::Asia__Kolkata);
+    assert_eq!(
+        zoned.utc_offset(),
+        Duration::hours(5) + Duration::minutes(30)
+    );
+}
+
+#[test]
+fn test_zoned_clock_from_unix_seconds() {
+    let zoned = ZonedClock::from_unix_seconds(0, Tz::Europe__Paris).unwrap();
+    assert_eq!(zoned.to_string(), "1970-01-01 01:00 +01:00 Europe/Paris");
+    assert_eq!(zoned.unix_seconds(), 0);
+}
+
+//
+// DST Gaps
+//
+
+#[test]
+fn test_time_in_spring_forward_gap_does_not_exist() {
+    // New York goes from 02:00 EST to 03:00 EDT on 10 March 2024.
+    let result = ZonedClock::new(date(2024, 3, 10), Clock::new(2, 30), Tz::America__New_York);
+    assert_eq!(result, Err(LocalTimeError::Nonexistent));
+}
+
+#[test]
+fn test_resolve_spring_forward_gap() {
+    let (day, clock, tz) = (date(2024, 3, 10), Clock::new(2, 30), Tz::America__New_York);
+    let earlier = ZonedClock::resolve(day, clock, tz, Disambiguation::Earlier);
+    assert_eq!(
+        earlier.to_string(),
+        "2024-03-10 01:30 -05:00 America/New_York"
+    );
+    let later = ZonedClock::resolve(day, clock, tz, Disambiguation::Later);
+    assert_eq!(
+        later.to_string(),
+        "2024-03-10 03:30 -04:00 America/New_York"
+    );
+    assert_eq!(later - earlier, Duration::hours(1));
+}
+
+#[test]
+fn test_resolve_half_hour_gap() {
+    // Lord Howe Island goes from 02:00 to 02:30 on 6 October 2024.
+    let (day, tz) = (date(2024, 10, 6), Tz::Australia__Lord_Howe);
+    let earlier = ZonedClock::resolve(day, Clock::new(2, 15), tz, Disambiguation::Earlier);
+    assert_eq!(
+        earlier.to_string(),
+        "2024-10-06 01:45 +10:30 Australia/Lord_Howe"
+    );
+    let later = ZonedClock::resolve(day, Clock::new(2, 15), tz, Disambiguation::Later);
+    assert_eq!(
+        later.to_string(),
+        "2024-10-06 02:45 +11:00 Australia/Lord_Howe"
+    );
+    assert!(ZonedClock::new(day, Clock::new(2, 30), tz).is_ok());
+}
+
+#[test]
+fn test_resolve_gap_in_back_to_back_transitions() {
+    // Samoa skipped 30 December 2011, going from -10:00 to +14:00.
+    let (day, clock, tz) = (date(2011, 12, 30), Clock::new(12, 0), Tz::Pacific__Apia);
+    assert_eq!(
+        ZonedClock::new(day, clock, tz),
+        Err(LocalTimeError::Nonexistent)
+    );
+    let earlier = ZonedClock::resolve(day, clock, tz, Disambiguation::Earlier);
+    assert_eq!(earlier.to_string(), "2011-12-29 12:00 -10:00 Pacific/Apia");
+    let later = ZonedClock::resolve(day, clock, tz, Disambiguation::Later);
+    assert_eq!(later.to_string(), "2011-12-31 12:00 +14:00 Pacific/Apia");
+}
+
+//
+// DST Overlaps
+//
+
+#[test]
+fn test_time_in_fall_back_overlap_is_ambiguous() {
+    // New York goes from 02:00 EDT back to 01:00 EST on 3 November 2024.
+    let result = ZonedClock::new(date(2024, 11, 3), Clock::new(1, 30), Tz::America__New_York);
+    match result {
+        Err(LocalTimeError::Ambiguous { earlier, later }) => {
+            assert_eq!(earlier.utc_offset(), Duration::hours(-4));
+            assert_eq!(later.utc_offset(), Duration::hours(-5));
+            assert_eq!(later - earlier, Duration::hours(1));
+        }
+        other => panic!("expected an ambiguous time, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_resolve_fall_back_overlap() {
+    let (day, clock, tz) = (date(2024, 10, 27), Clock::new(2, 30), Tz::Europe__Berlin);
+    let earlier = ZonedClock::resolve(day, clock, tz, Disambiguation::Earlier);
+    let later = ZonedClock::resolve(day, clock, tz, Disambiguation::Later);
+    assert_eq!(earlier.to_string(), "2024-10-27 02:30 +02:00 Europe/Berlin");
+    assert_eq!(later.to_string(), "2024-10-27 02:30 +01:00 Europe/Berlin");
+    assert!(earlier < later);
+}
+
+#[test]
+fn test_resolve_unambiguous_time_ignores_disambiguation() {
+    let (day, clock, tz) = (date(2024, 6, 1), Clock::new(12, 0), Tz::Europe__London);
+    assert_eq!(
+        ZonedClock::resolve(day, clock, tz, Disambiguation::Earlier),
+        ZonedClock::resolve(day, clock, tz, Disambiguation::Later)
+    );
+}
+
+//
+// Zone Conversion
+//
+
+#[test]
+fn test_convert_between_zones() {
+    let new_york =
+        ZonedClock::new(date(2024, 12, 31), Clock::new(20, 0), Tz::America__New_York).unwrap();
+    let tokyo = new_york.with_tz(Tz::Asia__Tokyo);
+    assert_eq!(tokyo.to_string(), "2025-01-01 10:00 +09:00 Asia/Tokyo");
+    assert_eq!(tokyo, new_york);
+    assert_eq!(
+        tokyo.with_tz(Tz::America__New_York).to_string(),
+        new_york.to_string()
+    );
+}
+
+#[test]
+fn test_convert_when_only_one_zone_is_on_dst() {
+    // The United States switch three weeks before Europe.
+    let new_york =
+        ZonedClock::new(date(2024, 3, 20), Clock::new(12, 0), Tz::America__New_York).unwrap();
+    assert_eq!(
+        new_york.with_tz(Tz::Europe__London).clock(),
+        Clock::new(16, 0)
+    );
+    let new_york =
+        ZonedClock::new(date(2024, 4, 20), Clock::new(12, 0), Tz::America__New_York).unwrap();
+    assert_eq!(
+        new_york.with_tz(Tz::Europe__London).clock(),
+        Clock::new(17, 0)
+    );
+}
+
+//
+// Arithmetic
+//
+
+#[test]
+fn test_add_duration_across_spring_forward() {
+    let noon = ZonedClock::new(date(2024, 3, 9), Clock::new(12, 0), Tz::America__New_York).unwrap();
+    let next = noon + Duration::hours(24);
+    assert_eq!(
+        (next.date(), next.clock()),
+        (date(2024, 3, 10), Clock::new(13, 0))
+    );
+    assert_eq!(next - Duration::hours(24), noon);
+}
+
+#[test]
+fn test_add_duration_across_fall_back() {
+    let one = ZonedClock::resolve(
+        date(2024, 11, 3),
+        Clock::new(1, 30),
+        Tz::America__New_York,
+        Disambiguation::Earlier,
+    );
+    let later = one + Duration::hours(1);
+    assert_eq!(later.clock(), Clock::new(1, 30));
+    assert_eq!(later.utc_offset(), Duration::hours(-5));
+}
+
+#[test]
+fn test_difference_between_zoned_clocks() {
+    let day = date(2024, 3, 10);
+    let midnight = ZonedClock::new(day, Clock::new(0, 0), Tz::America__New_York).unwrap();
+    let noon = ZonedClock::new(day, Clock::new(12, 0), Tz::America__New_York).unwrap();
+    assert_eq!(noon - midnight, Duration::hours(11));
+    assert_eq!(midnight - noon, Duration::hours(-11));
+}
@@ -0,0 +1,92 @@
+//! Compare the pointer list with the arena list on the usual workloads:
+//! pushing and popping at the ends, inserting and taking through a cursor,
+//! and iterating.
+
+use criterion::{criterion_group, criterion_main, Bencher, Criterion};
+use doubly_linked_list::{arena, LinkedList};
+
+const LEN: u64 = 10_000;
+
+// The two lists have the same methods but no trait in common, so the
+// workloads are written once and instantiated for each.
+macro_rules! workloads {
+    ($module:ident, $list:ty) => {
+        mod $module {
+            use super::*;
+
+            pub fn push_pop(b: &mut Bencher) {
+                b.iter(|| {
+                    let mut list = <$list>::new();
+                    for n in 0..LEN {
+                        list.push_back(n);
+                        list.push_front(n);
+                    }
+                    let mut sum = 0;
+                    while let (Some(front), Some(back)) = (list.pop_front(), list.pop_back()) {
+                        sum += front ^ back;
+                    }
+                    sum
+                })
+            }
+
+            pub fn cursor_insert(b: &mut Bencher) {
+                b.iter(|| {
+                    let mut list = (0..LEN).collect::<$list>();
+                    let mut cursor = list.cursor_front();
+                    cursor.seek_forward(LEN as usize / 2);
+                    for n in 0..LEN {
+                        cursor.insert_after(n);
+                        cursor.insert_before(n);
+                        cursor.next();
+                    }
+                    list.len()
+                })
+            }
+
+            pub fn cursor_take(b: &mut Bencher) {
+                b.iter(|| {
+                    let mut list = (0..LEN).collect::<$list>();
+                    let mut cursor = list.cursor_front();
+                    cursor.seek_forward(LEN as usize / 2);
+                    let mut sum = 0;
+                    while let Some(n) = cursor.take() {
+                        sum += n;
+                    }
+                    sum
+                })
+            }
+
+            pub fn iterate(b: &mut Bencher) {
+                let list = (0..LEN).collect::<$list>();
+                b.iter(|| list.iter().sum::<u64>())
+            }
+        }
+    };
+}
+
+workloads!(pointer, LinkedList<u64>);
+workloads!(arena_list, arena::LinkedList<u64>);
+
+type Workload = fn(&mut Bencher);
+
+fn lists(c: &mut Criterion) {
+    let workloads: [(&str, Workload, Workload); 4] = [
+        ("push_pop", pointer::push_pop, arena_list::push_pop),
+        (
+            "cursor_insert",
+            pointer::cursor_insert,
+            arena_list::cursor_insert,
+        ),
+        ("cursor_take", pointer::cursor_take, arena_list::cursor_take),
+        ("iterate", pointer::iterate, arena_list::iterate),
+    ];
+    for (name, pointer, arena) in workloads {
+        let mut group = c.benchmark_group(name);
+        group.bench_function("pointer", pointer);
+        group.bench_function("arena", arena);
+        group.finish();
+    }
+}
+
+criterion_group!(benches, lists);
+criterion_main!(benches);
@@ -0,0 +1,425 @@
+//! The same list in safe code: the nodes live in a `Vec` of slots and link to
+//! each other by slot index instead of by pointer.
+//!
+//! Freed slots are reused, and each slot counts how many times it has been
+//! freed: its generation. A `Handle` is a slot index with the generation it
+//! was created in, so a handle to an element that has since been removed is
+//! recognised as stale, even after its slot is reused. The generation wraps
+//! around after 2^32 reuses of the same slot.
+
+use std::mem;
+
+pub struct LinkedList<T> {
+    slots: Vec<Slot<T>>,
+    // the first slot of the free list, chained through `Entry::Free`
+    free: Option<usize>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+enum Entry<T> {
+    Occupied(Node<T>),
+    Free { next_free: Option<usize> },
+}
+
+struct Node<T> {
+    element: T,
+    previous: Option<usize>,
+    next: Option<usize>,
+}
+
+/// A stable reference to an element of a list, valid until the element is
+/// removed, wherever other elements are inserted or removed.
+///
+/// Handles are only meaningful for the list that gave them out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u32,
+}
+
+pub struct Cursor<'a, T> {
+    node: Option<usize>,
+    list: &'a mut LinkedList<T>,
+}
+
+pub struct Iter<'a, T> {
+    list: &'a LinkedList<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Return an empty list with room for `capacity` elements
+    pub fn with_capacity(capacity: usize) -> Self {
+        LinkedList {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            front: None,
+            back: None,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return a cursor positioned on the front element
+    pub fn cursor_front(&mut self) -> Cursor<'_, T> {
+        Cursor {
+            node: self.front,
+            list: self,
+        }
+    }
+
+    /// Return a cursor positioned on the back element
+    pub fn cursor_back(&mut self) -> Cursor<'_, T> {
+        Cursor {
+            node: self.back,
+            list: self,
+        }
+    }
+
+    /// Return a cursor positioned on the element of `handle`, unless it has
+    /// been removed
+    pub fn cursor_at(&mut self, handle: Handle) -> Option<Cursor<'_, T>> {
+        let node = self.slot_of(handle)?;
+        Some(Cursor {
+            node: Some(node),
+            list: self,
+        })
+    }
+
+    /// Return an iterator that moves from front to back
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+
+    pub fn push_back(&mut self, element: T) -> Handle {
+        let slot = self.link(element, self.back, None);
+        self.handle(slot)
+    }
+
+    pub fn push_front(&mut self, element: T) -> Handle {
+        let slot = self.link(element, None, self.front);
+        self.handle(slot)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.back.map(|slot| self.unlink(slot))
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.front.map(|slot| self.unlink(slot))
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.front.map(|slot| &self.node(slot).element)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.back.map(|slot| &self.node(slot).element)
+    }
+
+    /// The element of `handle`, unless it has been removed
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.slot_of(handle).map(|slot| &self.node(slot).element)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.slot_of(handle)
+            .map(|slot| &mut self.node_mut(slot).element)
+    }
+
+    /// Remove and return the element of `handle`, unless it has already
+    /// been removed
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        self.slot_of(handle).map(|slot| self.unlink(slot))
+    }
+
+    /// Move the element of `handle` to the front, keeping its handle.
+    /// Return false if it has been removed.
+    pub fn move_to_front(&mut self, handle: Handle) -> bool {
+        let Some(slot) = self.slot_of(handle) else {
+            return false;
+        };
+        if self.front != Some(slot) {
+            self.detach(slot);
+            self.attach(slot, None, self.front);
+        }
+        true
+    }
+
+    /// Move the element of `handle` to the back, keeping its handle.
+    /// Return false if it has been removed.
+    pub fn move_to_back(&mut self, handle: Handle) -> bool {
+        let Some(slot) = self.slot_of(handle) else {
+            return false;
+        };
+        if self.back != Some(slot) {
+            self.detach(slot);
+            self.attach(slot, self.back, None);
+        }
+        true
+    }
+
+    fn slot_of(&self, handle: Handle) -> Option<usize> {
+        match self.slots.get(handle.slot) {
+            Some(Slot {
+                generation,
+                entry: Entry::Occupied(_),
+            }) if *generation == handle.generation => Some(handle.slot),
+            _ => None,
+        }
+    }
+
+    fn handle(&self, slot: usize) -> Handle {
+        Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    // Links only ever point at occupied slots.
+    fn node(&self, slot: usize) -> &Node<T> {
+        match &self.slots[slot].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    fn node_mut(&mut self, slot: usize) -> &mut Node<T> {
+        match &mut self.slots[slot].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked to a free slot"),
+        }
+    }
+
+    /// Store `element` in a free slot, linked in between `previous` and
+    /// `next`, which must be neighbours or ends of the list
+    fn link(&mut self, element: T, previous: Option<usize>, next: Option<usize>) -> usize {
+        let node = Entry::Occupied(Node {
+            element,
+            previous: None,
+            next: None,
+        });
+        let slot = match self.free {
+            Some(slot) => {
+                match mem::replace(&mut self.slots[slot].entry, node) {
+                    Entry::Free { next_free } => self.free = next_free,
+                    Entry::Occupied(_) => unreachable!("free list through an occupied slot"),
+                }
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry: node,
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.attach(slot, previous, next);
+        self.len += 1;
+        slot
+    }
+
+    /// Unlink the node in `slot`, free the slot and return the element
+    fn unlink(&mut self, slot: usize) -> T {
+        self.detach(slot);
+        let Slot { generation, entry } = &mut self.slots[slot];
+        *generation = generation.wrapping_add(1);
+        let node = match mem::replace(
+            entry,
+            Entry::Free {
+                next_free: self.free,
+            },
+        ) {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("unlinked a free slot"),
+        };
+        self.free = Some(slot);
+        self.len -= 1;
+        node.element
+    }
+
+    /// Link the detached node in `slot` in between `previous` and `next`,
+    /// which must be neighbours or ends of the list
+    fn attach(&mut self, slot: usize, previous: Option<usize>, next: Option<usize>) {
+        let node = self.node_mut(slot);
+        node.previous = previous;
+        node.next = next;
+        match previous {
+            Some(previous) => self.node_mut(previous).next = Some(slot),
+            None => self.front = Some(slot),
+        }
+        match next {
+            Some(next) => self.node_mut(next).previous = Some(slot),
+            None => self.back = Some(slot),
+        }
+    }
+
+    /// Link the neighbours of the node in `slot` to each other, leaving the
+    /// node out of the list but in its slot
+    fn detach(&mut self, slot: usize) {
+        let node = self.node(slot);
+        let (previous, next) = (node.previous, node.next);
+        match previous {
+            Some(previous) => self.node_mut(previous).next = next,
+            None => self.front = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).previous = previous,
+            None => self.back = previous,
+        }
+    }
+}
+
+// the cursor is expected to act as if it is at the position of an element
+// and it also has to work with and be able to insert into an empty list.
+impl<T> Cursor<'_, T> {
+    /// Take a mutable reference to the current element
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.node.map(|slot| &mut self.list.node_mut(slot).element)
+    }
+
+    /// The handle of the current element
+    pub fn handle(&self) -> Option<Handle> {
+        self.node.map(|slot| self.list.handle(slot))
+    }
+
+    /// Move one position forward (towards the back) and
+    /// return a reference to the new position
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut T> {
+        self.node = Some(self.list.node(self.node?).next?);
+        self.peek_mut()
+    }
+
+    /// Move one position backward (towards the front) and
+    /// return a reference to the new position
+    pub fn prev(&mut self) -> Option<&mut T> {
+        self.node = Some(self.list.node(self.node?).previous?);
+        self.peek_mut()
+    }
+
+    /// Remove and return the element at the current position and move the cursor
+    /// to the neighboring element that's closest to the back. This can be
+    /// either the next or previous position.
+    pub fn take(&mut self) -> Option<T> {
+        let slot = self.node?;
+        let node = self.list.node(slot);
+        self.node = node.next.or(node.previous);
+        Some(self.list.unlink(slot))
+    }
+
+    pub fn insert_after(&mut self, element: T) {
+        match self.node {
+            Some(slot) => {
+                let next = self.list.node(slot).next;
+                self.list.link(element, Some(slot), next);
+            }
+            None => self.node = Some(self.list.link(element, None, None)),
+        }
+    }
+
+    pub fn insert_before(&mut self, element: T) {
+        match self.node {
+            Some(slot) => {
+                let previous = self.list.node(slot).previous;
+                self.list.link(element, previous, Some(slot));
+            }
+            None => self.node = Some(self.list.link(element, None, None)),
+        }
+    }
+
+    pub fn seek_forward(&mut self, n: usize) -> bool {
+        (0..n).all(|_| self.next().is_some())
+    }
+
+    pub fn seek_backward(&mut self, n: usize) -> bool {
+        (0..n).all(|_| self.prev().is_some())
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.front?);
+        self.front = node.next;
+        self.len -= 1;
+        Some(&node.element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.back?);
+        self.back = node.previous;
+        self.len -= 1;
+        Some(&node.element)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        let mut list = Self::with_capacity(iter.size_hint().0);
+        for element in iter {
+            list.push_back(element);
+        }
+        list
+    }
+}
@@ -0,0 +1,201 @@
+//! A deque that threads can share, with a lock per node instead of one for
+//! the whole list, so that the two ends can be worked on at once.
+//!
+//! The list runs between two sentinel nodes that are never removed. Nodes are
+//! always locked from the front to the back, so that no two threads can wait
+//! for each other. The back end finds its nodes by reading the links before
+//! locking them, then checks under the locks that they are still neighbours,
+//! and starts again if not.
+//!
+//! This is the fine-grained locking baseline; it is not lock-free. Under
+//! `--cfg loom`, it is built on loom's `Arc` and `Mutex`, so that the loom
+//! tests can explore its interleavings.
+
+#[cfg(loom)]
+use loom::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+#[cfg(not(loom))]
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+
+use std::sync::PoisonError;
+
+pub struct Deque<T> {
+    front: Arc<Node<T>>,
+    back: Arc<Node<T>>,
+    len: AtomicUsize,
+}
+
+struct Node<T> {
+    links: Mutex<Links<T>>,
+}
+
+// Neighbours hold each other with `Arc`s both ways, since loom has no `Weak`:
+// the links of a node are cleared when it is unlinked, and the sentinels'
+// when the deque is dropped, which breaks the cycles.
+struct Links<T> {
+    // `None` in the sentinels
+    element: Option<T>,
+    previous: Option<Arc<Node<T>>>,
+    next: Option<Arc<Node<T>>>,
+}
+
+impl<T> Node<T> {
+    fn new(
+        element: Option<T>,
+        previous: Option<Arc<Node<T>>>,
+        next: Option<Arc<Node<T>>>,
+    ) -> Arc<Self> {
+        Arc::new(Node {
+            links: Mutex::new(Links {
+                element,
+                previous,
+                next,
+            }),
+        })
+    }
+
+    // Nothing panics with a lock held but an allocation failure, which
+    // leaves the links as they were, so a poisoned lock is still good.
+    fn lock(&self) -> MutexGuard<'_, Links<T>> {
+        self.links.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn is<T>(link: &Option<Arc<Node<T>>>, node: &Arc<Node<T>>) -> bool {
+    link.as_ref().is_some_and(|link| Arc::ptr_eq(link, node))
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deque<T> {
+    pub fn new() -> Self {
+        let front = Node::new(None, None, None);
+        let back = Node::new(None, Some(front.clone()), None);
+        front.lock().next = Some(back.clone());
+        Deque {
+            front,
+            back,
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of elements, which other threads may be changing
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push_front(&self, element: T) {
+        let mut front = self.front.lock();
+        let first = front
+            .next
+            .clone()
+            .expect("the front sentinel has a next node");
+        let mut first_links = first.lock();
+
+        let node = Node::new(Some(element), Some(self.front.clone()), Some(first.clone()));
+        first_links.previous = Some(node.clone());
+        front.next = Some(node);
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn pop_front(&self) -> Option<T> {
+        let mut front = self.front.lock();
+        let first = front
+            .next
+            .clone()
+            .expect("the front sentinel has a next node");
+        if Arc::ptr_eq(&first, &self.back) {
+            return None;
+        }
+        let mut first_links = first.lock();
+        let second = first_links.next.take().expect("an element has a next node");
+        let mut second_links = second.lock();
+
+        second_links.previous = Some(self.front.clone());
+        front.next = Some(second.clone());
+        first_links.previous = None;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        first_links.element.take()
+    }
+
+    pub fn push_back(&self, element: T) {
+        let mut element = Some(element);
+        loop {
+            let last = self.last();
+            let mut last_links = last.lock();
+            let mut back = self.back.lock();
+            // Holding the lock of `last` keeps it linked, if it still is.
+            if !is(&back.previous, &last) {
+                continue;
+            }
+
+            let node = Node::new(element.take(), Some(last.clone()), Some(self.back.clone()));
+            last_links.next = Some(node.clone());
+            back.previous = Some(node);
+            self.len.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        loop {
+            let last = self.last();
+            if Arc::ptr_eq(&last, &self.front) {
+                let _front = self.front.lock();
+                let back = self.back.lock();
+                if is(&back.previous, &self.front) {
+                    return None;
+                }
+                continue;
+            }
+
+            // `None` if another thread unlinked `last` meanwhile
+            let Some(before) = last.lock().previous.clone() else {
+                continue;
+            };
+            let mut before_links = before.lock();
+            let mut last_links = last.lock();
+            let mut back = self.back.lock();
+            if !is(&before_links.next, &last) || !is(&back.previous, &last) {
+                continue;
+            }
+
+            before_links.next = Some(self.back.clone());
+            back.previous = Some(before.clone());
+            last_links.previous = None;
+            last_links.next = None;
+            self.len.fetch_sub(1, Ordering::Relaxed);
+            return last_links.element.take();
+        }
+    }
+
+    /// The node before the back sentinel, as it was when read
+    fn last(&self) -> Arc<Node<T>> {
+        self.back
+            .lock()
+            .previous
+            .clone()
+            .expect("the back sentinel has a previous node")
+    }
+}
+
+impl<T> Drop for Deque<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+        self.front.lock().next = None;
+        self.back.lock().previous = None;
+    }
+}
@@ -3,6 +3,15 @@
 // You are free to use anything in it, but it's mainly for the test framework.
 mod pre_implemented;
 
+// A safe variant of the list, in an arena of slots instead of boxed nodes.
+pub mod arena;
+
+// A deque that threads can share, with a lock per node.
+pub mod concurrent;
+
+// A least-recently-used cache, on top of the arena list.
+pub mod lru;
+
 // Documentation links:
 // https://www.youtube.com/watch?v=TJOFSMpJdzg&ab_channel=JonGjengset
 //
@@ -26,6 +35,9 @@ mod pre_implemented;
 // https://www.youtube.com/watch?v=iVYWDIW71jk&ab_channel=JonGjengset
 // https://rust-unofficial.github.io/too-many-lists/sixth.html
 
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::mem;
 use std::ptr::NonNull;
 
 type NodePtr<T> = NonNull<Node<T>>;
@@ -35,7 +47,7 @@ pub struct LinkedList<T> {
     front: Link<T>,
     back: Link<T>,
     len: usize,
-    _marker: std::marker::PhantomData<T>,
+    _marker: PhantomData<T>,
 }
 
 // We don't have any shared mutable state nor any thread dependent data.
@@ -54,11 +66,33 @@ pub struct Cursor<'a, T> {
     list: &'a mut LinkedList<T>,
 }
 
+// The iterators walk in from both ends, and stop when `len` nodes have been
+// yielded, wherever the ends are.
 pub struct Iter<'a, T> {
-    node: Link<T>,
-    _marker: std::marker::PhantomData<&'a T>,
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
 }
 
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+// The iterators only hand out references to the elements:
+// same bounds as for slice::Iter and slice::IterMut
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
 impl<T> Node<T> {
     pub fn create_linkless(element: T) -> NodePtr<T> {
         unsafe {
@@ -72,7 +106,13 @@ impl<T> Node<T> {
 
     pub unsafe fn link(mut previous: NodePtr<T>, mut next: NodePtr<T>) {
         next.as_mut().previous = Some(previous);
-        previous.as_mut().next = Some(NonNull::from(next));
+        previous.as_mut().next = Some(next);
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -82,7 +122,7 @@ impl<T> LinkedList<T> {
             front: None,
             back: None,
             len: 0,
-            _marker: std::marker::PhantomData,
+            _marker: PhantomData,
         }
     }
 
@@ -121,27 +161,242 @@ impl<T> LinkedList<T> {
 
     /// Return an iterator that moves from front to back
     pub fn iter(&self) -> Iter<'_, T> {
-        unsafe {
-            if self.is_empty() {
-                return Iter {
-                    node: None,
-                    _marker: std::marker::PhantomData,
-                };
+        Iter {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return an iterator of mutable references that moves from front to back
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Move all the elements of `other` to the back of the list,
+    /// leaving `other` empty
+    pub fn append(&mut self, other: &mut Self) {
+        let Some((front, back, len)) = mem::take(other).into_parts() else {
+            return;
+        };
+        match self.back {
+            // SAFETY: the back of this list and the front of the other one
+            // are both valid and unlinked on that side
+            Some(self_back) => unsafe { Node::link(self_back, front) },
+            None => self.front = Some(front),
+        }
+        self.back = Some(back);
+        self.len += len;
+    }
+
+    /// Split the list in two at index `at`: the list keeps the elements
+    /// before it and the ones from `at` on are returned
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "cannot split off at a nonexistent index");
+        if at == 0 {
+            return mem::take(self);
+        }
+        if at == self.len {
+            return Self::new();
+        }
+
+        let last_kept = self.node_at(at - 1);
+        // SAFETY: `at < len`, so the last node kept has a successor, and
+        // both are valid nodes of this list
+        let first_split = unsafe {
+            let first_split = (*last_kept.as_ptr()).next.unwrap();
+            (*last_kept.as_ptr()).next = None;
+            (*first_split.as_ptr()).previous = None;
+            first_split
+        };
+
+        let split = LinkedList {
+            front: Some(first_split),
+            back: self.back,
+            len: self.len - at,
+            _marker: PhantomData,
+        };
+        self.back = Some(last_kept);
+        self.len = at;
+        split
+    }
+
+    /// Remove every element for which `keep` returns false, in order
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        let mut node = self.front;
+        while let Some(current) = node {
+            // SAFETY: `current` is still linked into the list: only the
+            // nodes before it have been unlinked
+            unsafe {
+                node = (*current.as_ptr()).next;
+                if !keep(&(*current.as_ptr()).element) {
+                    drop(self.unlink(current));
+                }
+            }
+        }
+    }
+
+    /// Sort the list in place, keeping equal elements in order
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Sort the list in place by the key `key` gives each element,
+    /// keeping elements with equal keys in order
+    pub fn sort_by_key<K: Ord>(&mut self, mut key: impl FnMut(&T) -> K) {
+        self.sort_by(|a, b| key(a).cmp(&key(b)));
+    }
+
+    /// Sort the list in place with `compare`, keeping equal elements in order
+    ///
+    /// This is a bottom-up merge sort relinking the nodes: O(n log n)
+    /// comparisons and no allocation. If `compare` panics, the elements
+    /// are leaked.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        // The list is left empty while the nodes are relinked, so that a
+        // panic leaks them rather than letting `Drop` walk broken links.
+        let Some((mut head, _, len)) = mem::take(self).into_parts() else {
+            return;
+        };
+
+        // Merge runs of `width` nodes in pairs, doubling `width` until a
+        // single merge covers the whole list.
+        let mut width = 1;
+        loop {
+            let mut merged_head: Link<T> = None;
+            let mut merged_tail: Link<T> = None;
+            let mut merges = 0;
+            let mut left = Some(head);
+
+            // SAFETY: every node is a valid node of the sorted list, and
+            // each one is appended to the merged list exactly once per pass
+            unsafe {
+                while let Some(left_start) = left {
+                    merges += 1;
+                    let mut right = Some(left_start);
+                    let mut left_len = 0;
+                    while let (true, Some(node)) = (left_len < width, right) {
+                        right = (*node.as_ptr()).next;
+                        left_len += 1;
+                    }
+                    let mut right_len = width;
+
+                    loop {
+                        let take_left = match (left, right) {
+                            (Some(_), Some(_)) if left_len > 0 && right_len > 0 => {
+                                let (l, r) = (left.unwrap(), right.unwrap());
+                                compare(&(*l.as_ptr()).element, &(*r.as_ptr()).element)
+                                    != Ordering::Greater
+                            }
+                            _ if left_len > 0 => true,
+                            (_, Some(_)) if right_len > 0 => false,
+                            _ => break,
+                        };
+                        let node = if take_left {
+                            let node = left.unwrap();
+                            left = (*node.as_ptr()).next;
+                            left_len -= 1;
+                            node
+                        } else {
+                            let node = right.unwrap();
+                            right = (*node.as_ptr()).next;
+                            right_len -= 1;
+                            node
+                        };
+
+                        match merged_tail {
+                            Some(tail) => (*tail.as_ptr()).next = Some(node),
+                            None => merged_head = Some(node),
+                        }
+                        (*node.as_ptr()).previous = merged_tail;
+                        merged_tail = Some(node);
+                    }
+
+                    left = right;
+                }
+                (*merged_tail.unwrap().as_ptr()).next = None;
+            }
+
+            head = merged_head.unwrap();
+            if merges == 1 {
+                self.front = Some(head);
+                self.back = merged_tail;
+                self.len = len;
+                return;
             }
+            width *= 2;
+        }
+    }
 
-            let current_node = self.front.unwrap().as_ref();
-            Iter {
-                node: Some(NonNull::from(current_node)),
-                _marker: std::marker::PhantomData,
+    /// The node at `index`, walking from the closest end
+    fn node_at(&self, index: usize) -> NodePtr<T> {
+        debug_assert!(index < self.len);
+        // SAFETY: the list has more than `index` nodes, all valid
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.front.unwrap();
+                for _ in 0..index {
+                    node = (*node.as_ptr()).next.unwrap();
+                }
+                node
+            } else {
+                let mut node = self.back.unwrap();
+                for _ in index + 1..self.len {
+                    node = (*node.as_ptr()).previous.unwrap();
+                }
+                node
             }
         }
     }
+
+    /// Unlink `node` from the list and take back ownership of it
+    ///
+    /// Safety: `node` must be a node of this list
+    unsafe fn unlink(&mut self, node: NodePtr<T>) -> Box<Node<T>> {
+        let mut node = Box::from_raw(node.as_ptr());
+        match node.previous {
+            Some(previous) => (*previous.as_ptr()).next = node.next,
+            None => self.front = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).previous = node.previous,
+            None => self.back = node.previous,
+        }
+        node.previous = None;
+        node.next = None;
+        self.len -= 1;
+        node
+    }
+
+    /// Give up ownership of the nodes: the front, the back and the length,
+    /// unless the list is empty
+    fn into_parts(mut self) -> Option<(NodePtr<T>, NodePtr<T>, usize)> {
+        let parts = self
+            .front
+            .zip(self.back)
+            .map(|(front, back)| (front, back, self.len));
+        self.front = None;
+        self.back = None;
+        self.len = 0;
+        parts
+    }
 }
 
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
         let mut cursor = self.cursor_front();
-        while let Some(_) = cursor.take() {}
+        while cursor.take().is_some() {}
     }
 }
 
@@ -156,7 +411,7 @@ impl<T> Cursor<'_, T> {
     /// Take a mutable reference to the current element
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         self.node
-            .and_then(|node_ptr| unsafe { Some(&mut (*node_ptr.as_ptr()).element) })
+            .map(|node_ptr| unsafe { &mut (*node_ptr.as_ptr()).element })
     }
 
     fn _peek_mut(&mut self, node: Option<NonNull<Node<T>>>) -> Option<&mut T> {
@@ -185,30 +440,27 @@ impl<T> Cursor<'_, T> {
     /// to the neighboring element that's closest to the back. This can be
     /// either the next or previous position.
     pub fn take(&mut self) -> Option<T> {
-        if self.list.is_empty() {
-            return None;
-        }
-
-        let mut node = self.node?;
-        let &mut Node { previous, next, .. } = unsafe { node.as_mut() };
+        self.unlink_current().map(|node| node.element)
+    }
 
-        let target_node = next.or(previous);
-        self.node = target_node;
+    /// Like `take`, but return the element in a list of its own
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T>> {
+        let node = NonNull::from(Box::leak(self.unlink_current()?));
+        Some(LinkedList {
+            front: Some(node),
+            back: Some(node),
+            len: 1,
+            _marker: PhantomData,
+        })
+    }
 
+    fn unlink_current(&mut self) -> Option<Box<Node<T>>> {
+        let node = self.node?;
+        // SAFETY: the cursor is always on a node of its list
         unsafe {
-            match previous {
-                Some(mut previous_ptr) => previous_ptr.as_mut().next = next,
-                None => self.list.front = self.node,
-            }
-            match next {
-                Some(mut next_ptr) => next_ptr.as_mut().previous = previous,
-                None => self.list.back = self.node,
-            }
+            self.node = node.as_ref().next.or(node.as_ref().previous);
+            Some(self.list.unlink(node))
         }
-
-        self.list.len -= 1;
-
-        unsafe { Some(Box::from_raw(node.as_mut() as *mut Node<T>).element) }
     }
 
     pub fn insert_after(&mut self, element: T) {
@@ -269,21 +521,178 @@ impl<T> Cursor<'_, T> {
 
         self.list.len += 1;
     }
+
+    /// Move all the elements of `other` in after the current position.
+    /// On an empty list, the cursor ends on the front of `other`.
+    pub fn splice_after(&mut self, other: LinkedList<T>) {
+        let Some((front, back, len)) = other.into_parts() else {
+            return;
+        };
+        match self.node {
+            // SAFETY: the cursor node and the spliced ends are valid nodes,
+            // and the cursor's successor is read before it is relinked
+            Some(node) => unsafe {
+                match node.as_ref().next {
+                    Some(next) => Node::link(back, next),
+                    None => self.list.back = Some(back),
+                }
+                Node::link(node, front);
+            },
+            None => {
+                self.node = Some(front);
+                self.list.front = Some(front);
+                self.list.back = Some(back);
+            }
+        }
+        self.list.len += len;
+    }
+
+    /// Move all the elements of `other` in before the current position.
+    /// On an empty list, the cursor ends on the back of `other`.
+    pub fn splice_before(&mut self, other: LinkedList<T>) {
+        let Some((front, back, len)) = other.into_parts() else {
+            return;
+        };
+        match self.node {
+            // SAFETY: as in `splice_after`
+            Some(node) => unsafe {
+                match node.as_ref().previous {
+                    Some(previous) => Node::link(previous, front),
+                    None => self.list.front = Some(front),
+                }
+                Node::link(back, node);
+            },
+            None => {
+                self.node = Some(back);
+                self.list.front = Some(front);
+                self.list.back = Some(back);
+            }
+        }
+        self.list.len += len;
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
-        if self.node.is_none() {
+        if self.len == 0 {
             return None;
         }
+        // SAFETY: nodes remain between the ends, and the shared borrow of
+        // the list keeps them alive and unchanged
+        unsafe {
+            let node = self.front?.as_ptr();
+            self.front = (*node).next;
+            self.len -= 1;
+            Some(&(*node).element)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: as in `next`
+        unsafe {
+            let node = self.back?.as_ptr();
+            self.back = (*node).previous;
+            self.len -= 1;
+            Some(&(*node).element)
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
 
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: nodes remain between the ends, the mutable borrow of the
+        // list keeps them alive, and each element is handed out only once
         unsafe {
-            let res = &(*self.node.unwrap().as_ptr()).element;
-            self.node = (*self.node.unwrap().as_ptr()).next;
+            let node = self.front?.as_ptr();
+            self.front = (*node).next;
+            self.len -= 1;
+            Some(&mut (*node).element)
+        }
+    }
 
-            Some(res)
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
         }
+        // SAFETY: as in `next`
+        unsafe {
+            let node = self.back?.as_ptr();
+            self.back = (*node).previous;
+            self.len -= 1;
+            Some(&mut (*node).element)
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
     }
 }
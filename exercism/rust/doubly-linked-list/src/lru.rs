@@ -0,0 +1,235 @@
+//! A least-recently-used cache: a `HashMap` from keys to handles into an
+//! arena list of the entries, kept in order of use.
+//!
+//! The least recently used entry is at the front of the list and the most
+//! recently used at the back, so that looking up, inserting and evicting an
+//! entry are all O(1): the map finds its node, and the handle moves it to the
+//! back or takes it out without walking the list.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::arena::{Handle, LinkedList};
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+}
+
+// `Send`, so that a cache can move to another thread with its closures
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send>;
+type EvictionCallback<K, V> = Box<dyn FnMut(K, V) + Send>;
+
+/// A map that holds at most `capacity` entries, and optionally at most a
+/// total weight of them, evicting the least recently used entries to make
+/// room for new ones.
+pub struct LruCache<K, V> {
+    map: HashMap<K, Handle>,
+    entries: LinkedList<Entry<K, V>>,
+    capacity: usize,
+    max_weight: usize,
+    weight: usize,
+    weigher: Option<Weigher<K, V>>,
+    on_evict: Option<EvictionCallback<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Return an empty cache holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            entries: LinkedList::with_capacity(capacity),
+            capacity,
+            max_weight: usize::MAX,
+            weight: 0,
+            weigher: None,
+            on_evict: None,
+        }
+    }
+
+    /// Also evict entries while the weights `weigher` gives them add up to
+    /// more than `max_weight`
+    ///
+    /// An entry heavier than `max_weight` on its own is evicted as soon as
+    /// it is put in.
+    pub fn with_max_weight(
+        mut self,
+        max_weight: usize,
+        weigher: impl Fn(&K, &V) -> usize + Send + 'static,
+    ) -> Self {
+        self.max_weight = max_weight;
+        self.weigher = Some(Box::new(weigher));
+        self
+    }
+
+    /// Call `on_evict` with each entry evicted to make room, but not with
+    /// the ones removed or replaced
+    pub fn with_eviction_callback(mut self, on_evict: impl FnMut(K, V) + Send + 'static) -> Self {
+        self.on_evict = Some(Box::new(on_evict));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The total weight of the entries, or their number without a weigher
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return the value of `key`, marking it as the most recently used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Like `get`, for changing the value in place
+    ///
+    /// The weight of the entry is the one it was put in with.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        self.entries.move_to_back(handle);
+        self.entries.get_mut(handle).map(|entry| &mut entry.value)
+    }
+
+    /// Return the value of `key` without marking it as used
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        self.entries.get(handle).map(|entry| &entry.value)
+    }
+
+    /// Insert `value` under `key` as the most recently used entry, then
+    /// evict entries until the cache is within its limits
+    ///
+    /// Return the value `key` had before, if any.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let weight = self.weigh(&key, &value);
+        let old = match self.map.get(&key) {
+            Some(&handle) => {
+                self.entries.move_to_back(handle);
+                let entry = self
+                    .entries
+                    .get_mut(handle)
+                    .expect("mapped keys are in the list");
+                self.weight = self.weight - entry.weight + weight;
+                entry.weight = weight;
+                Some(std::mem::replace(&mut entry.value, value))
+            }
+            None => {
+                let handle = self.entries.push_back(Entry {
+                    key: key.clone(),
+                    value,
+                    weight,
+                });
+                self.map.insert(key, handle);
+                self.weight += weight;
+                None
+            }
+        };
+        self.evict();
+        old
+    }
+
+    /// Remove and return the value of `key`
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.remove(key)?;
+        let entry = self
+            .entries
+            .remove(handle)
+            .expect("mapped keys are in the list");
+        self.weight -= entry.weight;
+        Some(entry.value)
+    }
+
+    /// Change the number of entries held, evicting entries if it shrinks
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Remove all the entries, without calling the eviction callback
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.entries = LinkedList::new();
+        self.weight = 0;
+    }
+
+    /// Return an iterator over the entries, from the most to the least
+    /// recently used
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    fn weigh(&self, key: &K, value: &V) -> usize {
+        match &self.weigher {
+            Some(weigher) => weigher(key, value),
+            None => 1,
+        }
+    }
+
+    /// Evict the least recently used entries until the cache is within its
+    /// limits
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity || self.weight > self.max_weight {
+            let Some(entry) = self.entries.pop_front() else {
+                break;
+            };
+            self.map.remove(&entry.key);
+            self.weight -= entry.weight;
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(entry.key, entry.value);
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.entries
+                    .iter()
+                    .rev()
+                    .map(|entry| (&entry.key, &entry.value)),
+            )
+            .finish()
+    }
+}
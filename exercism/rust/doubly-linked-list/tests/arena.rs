@@ -0,0 +1,163 @@
+use doubly_linked_list::arena::*;
+
+// ———————————————————————————————————————————————————————————
+// The cursor API, as for the pointer list
+// ———————————————————————————————————————————————————————————
+
+#[test]
+fn basics_push_pop() {
+    let mut list = LinkedList::new();
+    assert!(list.is_empty());
+    list.push_back(1);
+    list.push_front(0);
+    list.push_back(2);
+    assert_eq!(list.len(), 3);
+    assert_eq!((list.front(), list.back()), (Some(&0), Some(&2)));
+    assert_eq!(list.pop_front(), Some(0));
+    assert_eq!(list.pop_back(), Some(2));
+    assert_eq!(list.pop_back(), Some(1));
+    assert_eq!(list.pop_front(), None);
+    assert!(list.is_empty());
+}
+
+#[test]
+fn iter_both_ways() {
+    let list = (0..10).collect::<LinkedList<_>>();
+    assert!((0..10).eq(list.iter().cloned()));
+    assert!((0..10).rev().eq(list.iter().rev().cloned()));
+    assert_eq!(list.iter().len(), 10);
+}
+
+#[test]
+fn cursor_insert_before_on_empty_list() {
+    let mut list = LinkedList::new();
+    list.cursor_front().insert_before(0);
+    assert_eq!(Some(0), list.pop_front());
+}
+
+#[test]
+fn cursor_insert_in_middle() {
+    let mut list = (0..10).collect::<LinkedList<_>>();
+    {
+        let mut cursor = list.cursor_front();
+        cursor.seek_forward(4);
+        for n in (0..10).map(|n| n * 10) {
+            cursor.insert_after(n);
+        }
+        cursor.seek_backward(1);
+        cursor.insert_before(-1);
+    }
+    let expected = (0..3)
+        .chain([-1, 3, 4])
+        .chain((0..10).rev().map(|n| n * 10))
+        .chain(5..10);
+    assert!(expected.eq(list.iter().cloned()));
+}
+
+#[test]
+fn cursor_next_prev_and_peek() {
+    let mut list = (0..10).collect::<LinkedList<_>>();
+    let mut cursor = list.cursor_front();
+    assert_eq!(cursor.peek_mut(), Some(&mut 0));
+    assert!(cursor.seek_forward(9));
+    assert_eq!(cursor.next(), None);
+    assert_eq!(cursor.peek_mut(), Some(&mut 9));
+    for n in (0..9).rev() {
+        assert_eq!(cursor.prev(), Some(&mut n.clone()));
+    }
+    assert!(!cursor.seek_backward(1));
+}
+
+#[test]
+fn cursor_take() {
+    let mut list = (0..10).collect::<LinkedList<_>>();
+    let mut cursor = list.cursor_front();
+    cursor.seek_forward(5);
+    for expected in (5..10).chain((0..5).rev()) {
+        assert_eq!(cursor.take(), Some(expected));
+    }
+    assert_eq!(cursor.take(), None);
+    assert!(list.is_empty());
+}
+
+// ———————————————————————————————————————————————————————————
+// Handles
+// ———————————————————————————————————————————————————————————
+
+#[test]
+fn handles_stay_valid_as_the_list_changes() {
+    let mut list = LinkedList::new();
+    let one = list.push_back("one");
+    let two = list.push_back("two");
+    list.push_front("zero");
+    list.cursor_front().insert_after("half");
+    assert_eq!(list.get(one), Some(&"one"));
+    *list.get_mut(two).unwrap() = "TWO";
+
+    let mut cursor = list.cursor_at(one).unwrap();
+    assert_eq!(cursor.next(), Some(&mut "TWO"));
+    assert_eq!(cursor.handle(), Some(two));
+    assert!(["zero", "half", "one", "TWO"].iter().eq(list.iter()));
+}
+
+#[test]
+fn removed_handles_are_stale() {
+    let mut list = (0..3).collect::<LinkedList<_>>();
+    let handle = list.push_back(3);
+    assert_eq!(list.remove(handle), Some(3));
+    assert_eq!(list.remove(handle), None);
+    assert_eq!(list.get(handle), None);
+    assert!(list.cursor_at(handle).is_none());
+
+    // the slot is reused, but the old handle doesn't see the new element
+    let new = list.push_front(4);
+    assert_eq!(list.get(handle), None);
+    assert_eq!(list.get(new), Some(&4));
+    assert!([4, 0, 1, 2].iter().eq(list.iter()));
+}
+
+#[test]
+fn taking_through_the_cursor_stales_the_handle() {
+    let mut list = LinkedList::new();
+    let handle = list.push_back('a');
+    list.push_back('b');
+    let mut cursor = list.cursor_at(handle).unwrap();
+    assert_eq!(cursor.take(), Some('a'));
+    assert_eq!(cursor.peek_mut(), Some(&mut 'b'));
+    assert_eq!(list.get(handle), None);
+}
+
+// checks that the arena frees slots for reuse instead of growing
+#[test]
+fn drop_each_element_once() {
+    use std::rc::Rc;
+
+    let counter = Rc::new(());
+    let mut list = LinkedList::new();
+    for _ in 0..100 {
+        list.push_back(Rc::clone(&counter));
+        list.push_front(Rc::clone(&counter));
+        list.pop_back();
+    }
+    assert_eq!(Rc::strong_count(&counter), 101);
+    drop(list);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn move_to_the_ends_keeps_handles() {
+    let mut list = LinkedList::new();
+    let handles = (0..4).map(|n| list.push_back(n)).collect::<Vec<_>>();
+    assert!(list.move_to_back(handles[1]));
+    assert!(list.move_to_front(handles[2]));
+    assert!(list.move_to_back(handles[3]));
+    assert!([2, 0, 1, 3].iter().eq(list.iter()));
+    assert!([3, 1, 0, 2].iter().eq(list.iter().rev()));
+    assert_eq!(list.get(handles[1]), Some(&1));
+
+    assert_eq!(list.remove(handles[0]), Some(0));
+    assert!(!list.move_to_front(handles[0]));
+    assert!(list.move_to_front(handles[1]));
+    assert!([1, 2, 3].iter().eq(list.iter()));
+    assert!([3, 2, 1].iter().eq(list.iter().rev()));
+}
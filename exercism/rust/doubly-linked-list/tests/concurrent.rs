@@ -0,0 +1,119 @@
+// Interleavings are checked exhaustively by the loom tests in `loom.rs`;
+// these run the deque on real threads, with many more operations.
+#![cfg(not(loom))]
+
+use doubly_linked_list::concurrent::Deque;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn deque_at_both_ends() {
+    let deque = Deque::new();
+    assert!(deque.is_empty());
+    deque.push_back(1);
+    deque.push_front(0);
+    deque.push_back(2);
+    assert_eq!(deque.len(), 3);
+    assert_eq!(deque.pop_back(), Some(2));
+    assert_eq!(deque.pop_front(), Some(0));
+    assert_eq!(deque.pop_front(), Some(1));
+    assert_eq!(deque.pop_front(), None);
+    assert_eq!(deque.pop_back(), None);
+}
+
+#[test]
+fn deque_drops_its_elements() {
+    let counter = Arc::new(());
+    let deque = Deque::new();
+    for _ in 0..10 {
+        deque.push_back(Arc::clone(&counter));
+        deque.push_front(Arc::clone(&counter));
+    }
+    deque.pop_back();
+    assert_eq!(Arc::strong_count(&counter), 20);
+    drop(deque);
+    assert_eq!(Arc::strong_count(&counter), 1);
+}
+
+#[test]
+fn deque_keeps_order_from_each_thread() {
+    const PER_THREAD: usize = 10_000;
+
+    let deque = Arc::new(Deque::new());
+    let threads = (0..4)
+        .map(|thread| {
+            let deque = Arc::clone(&deque);
+            thread::spawn(move || {
+                for n in 0..PER_THREAD {
+                    if thread % 2 == 0 {
+                        deque.push_back((thread, n));
+                    } else {
+                        deque.push_front((thread, n));
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(deque.len(), 4 * PER_THREAD);
+
+    // Each thread's elements come out from the front in the order pushed
+    // to the back, or in reverse for the front.
+    let mut last = [None; 4];
+    while let Some((thread, n)) = deque.pop_front() {
+        if let Some(previous) = last[thread] {
+            assert_eq!(
+                n,
+                if thread % 2 == 0 {
+                    previous + 1
+                } else {
+                    previous - 1
+                }
+            );
+        }
+        last[thread] = Some(n);
+    }
+    assert_eq!(
+        last,
+        [Some(PER_THREAD - 1), Some(0), Some(PER_THREAD - 1), Some(0)]
+    );
+}
+
+#[test]
+fn deque_pops_each_element_once() {
+    const LEN: usize = 20_000;
+
+    let deque = Arc::new((0..LEN).fold(Deque::new(), |deque, n| {
+        deque.push_back(n);
+        deque
+    }));
+    let threads = (0..4)
+        .map(|thread| {
+            let deque = Arc::clone(&deque);
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                loop {
+                    let element = if thread % 2 == 0 {
+                        deque.pop_front()
+                    } else {
+                        deque.pop_back()
+                    };
+                    match element {
+                        Some(n) => popped.push(n),
+                        None => return popped,
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut popped = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+    popped.sort_unstable();
+    assert_eq!(popped, (0..LEN).collect::<Vec<_>>());
+    assert!(deque.is_empty());
+}
@@ -409,6 +409,8 @@ fn drop_no_double_frees() {
     assert_eq!(counter.get(), N);
 }
 
+// too slow to run under Miri
+#[cfg_attr(miri, ignore)]
 #[test]
 fn drop_large_list_custom() {
     use std::time::Instant;
@@ -419,6 +421,8 @@ fn drop_large_list_custom() {
     println!("Time elapsed in expensive_function() is: {:?}", duration);
 }
 
+// too slow to run under Miri
+#[cfg_attr(miri, ignore)]
 #[test]
 fn drop_large_list() {
     drop((0..2_000_000).collect::<LinkedList<i32>>());
@@ -455,3 +459,247 @@ fn advanced_is_covariant() {
         i
     }
 }
+
+// ———————————————————————————————————————————————————————————
+// Tests for the extras: more iterators, split, splice and sort
+// ———————————————————————————————————————————————————————————
+
+// All of these also run under Miri (`cargo +nightly miri test`), which
+// checks the pointer juggling for undefined behaviour and leaks.
+
+fn to_vec<T: Clone>(list: &LinkedList<T>) -> Vec<T> {
+    list.iter().cloned().collect()
+}
+
+// checks the links both ways, not just the forward ones
+fn assert_list<T: Clone + PartialEq + std::fmt::Debug>(list: &LinkedList<T>, expected: &[T]) {
+    assert_eq!(list.len(), expected.len());
+    assert_eq!(to_vec(list), expected);
+    let mut backwards = list.iter().rev().cloned().collect::<Vec<_>>();
+    backwards.reverse();
+    assert_eq!(backwards, expected);
+}
+
+// iterators ————————————————————————————————————————————————
+#[test]
+fn iter_double_ended() {
+    let list = (0..6).collect::<LinkedList<_>>();
+    let mut iter = list.iter();
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.next(), Some(&0));
+    assert_eq!(iter.next_back(), Some(&5));
+    assert_eq!(iter.next_back(), Some(&4));
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next(), Some(&1));
+    assert_eq!(iter.next(), Some(&2));
+    assert_eq!(iter.next_back(), Some(&3));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn iter_mut() {
+    let mut list = (0..5).collect::<LinkedList<_>>();
+    for element in list.iter_mut() {
+        *element *= 10;
+    }
+    for element in (&mut list).into_iter().rev().take(2) {
+        *element += 1;
+    }
+    assert_list(&list, &[0, 10, 20, 31, 41]);
+}
+
+#[test]
+fn into_iter() {
+    let list = (0..5).map(|n| n.to_string()).collect::<LinkedList<_>>();
+    let mut iter = list.into_iter();
+    assert_eq!(iter.next_back().as_deref(), Some("4"));
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.collect::<Vec<_>>(), ["0", "1", "2", "3"]);
+}
+
+#[test]
+fn into_iter_drops_the_rest() {
+    let mut list = (0..5).map(|n| n.to_string()).collect::<LinkedList<_>>();
+    let mut iter = std::mem::take(&mut list).into_iter();
+    iter.next();
+    drop(iter);
+    assert!(list.is_empty());
+}
+
+// append and split_off —————————————————————————————————————
+#[test]
+fn append() {
+    let mut list = (0..3).collect::<LinkedList<_>>();
+    let mut other = (3..6).collect::<LinkedList<_>>();
+    list.append(&mut other);
+    assert_list(&list, &[0, 1, 2, 3, 4, 5]);
+    assert_list(&other, &[]);
+
+    let mut empty = LinkedList::new();
+    empty.append(&mut list);
+    list.append(&mut LinkedList::new());
+    assert_list(&empty, &[0, 1, 2, 3, 4, 5]);
+    assert_list(&list, &[]);
+}
+
+#[test]
+fn split_off() {
+    for at in 0..=6 {
+        let mut list = (0..6).collect::<LinkedList<_>>();
+        let split = list.split_off(at);
+        assert_list(&list, &(0..at).collect::<Vec<_>>());
+        assert_list(&split, &(at..6).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+#[should_panic]
+fn split_off_past_the_end() {
+    (0..3).collect::<LinkedList<_>>().split_off(4);
+}
+
+// splice and remove_current_as_list ————————————————————————
+#[test]
+fn cursor_splice_after() {
+    let mut list = (0..4).collect::<LinkedList<_>>();
+    let mut cursor = list.cursor_front();
+    cursor.seek_forward(1);
+    cursor.splice_after((10..13).collect());
+    assert_eq!(cursor.peek_mut(), Some(&mut 1));
+    assert_eq!(cursor.next(), Some(&mut 10));
+    cursor.seek_forward(4);
+    cursor.splice_after((20..22).collect());
+    cursor.splice_after(LinkedList::new());
+    assert_list(&list, &[0, 1, 10, 11, 12, 2, 3, 20, 21]);
+}
+
+#[test]
+fn cursor_splice_before() {
+    let mut list = (0..4).collect::<LinkedList<_>>();
+    let mut cursor = list.cursor_back();
+    cursor.seek_backward(1);
+    cursor.splice_before((10..13).collect());
+    assert_eq!(cursor.prev(), Some(&mut 12));
+    cursor.seek_backward(5);
+    cursor.splice_before((20..22).collect());
+    cursor.splice_before(LinkedList::new());
+    assert_list(&list, &[20, 21, 0, 1, 10, 11, 12, 2, 3]);
+}
+
+#[test]
+fn cursor_splice_into_empty_list() {
+    let mut list = LinkedList::new();
+    let mut cursor = list.cursor_front();
+    cursor.splice_after((0..3).collect());
+    assert_eq!(cursor.peek_mut(), Some(&mut 0));
+    assert_list(&list, &[0, 1, 2]);
+
+    let mut list = LinkedList::new();
+    let mut cursor = list.cursor_front();
+    cursor.splice_before((0..3).collect());
+    assert_eq!(cursor.peek_mut(), Some(&mut 2));
+    cursor.insert_after(3);
+    assert_list(&list, &[0, 1, 2, 3]);
+}
+
+#[test]
+fn cursor_remove_current_as_list() {
+    let mut list = (0..4).collect::<LinkedList<_>>();
+    let mut cursor = list.cursor_front();
+    cursor.seek_forward(1);
+    let removed = cursor.remove_current_as_list().unwrap();
+    assert_list(&removed, &[1]);
+    assert_eq!(cursor.peek_mut(), Some(&mut 2));
+
+    cursor.seek_forward(1);
+    let mut removed = cursor.remove_current_as_list().unwrap();
+    assert_eq!(cursor.peek_mut(), Some(&mut 2));
+    removed.push_front(30);
+    assert_list(&removed, &[30, 3]);
+    assert_list(&list, &[0, 2]);
+
+    let mut empty = LinkedList::<i32>::new();
+    assert!(empty.cursor_front().remove_current_as_list().is_none());
+}
+
+// sort and retain ——————————————————————————————————————————
+#[test]
+fn sort() {
+    // a simple generator, to sort lists of every length up to 40
+    let mut seed = 17_u32;
+    for len in 0..40 {
+        let values = (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                seed >> 16 & 0xff
+            })
+            .collect::<Vec<_>>();
+        let mut list = values.iter().cloned().collect::<LinkedList<_>>();
+        list.sort();
+
+        let mut sorted = values;
+        sorted.sort();
+        assert_list(&list, &sorted);
+    }
+}
+
+#[test]
+fn sort_is_stable() {
+    let mut list = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (3, 'f')]
+        .into_iter()
+        .collect::<LinkedList<_>>();
+    list.sort_by_key(|&(key, _)| key);
+    assert_list(
+        &list,
+        &[(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c'), (3, 'f')],
+    );
+    list.sort_by(|a, b| b.0.cmp(&a.0));
+    assert_list(
+        &list,
+        &[(3, 'a'), (3, 'c'), (3, 'f'), (2, 'd'), (1, 'b'), (1, 'e')],
+    );
+}
+
+#[test]
+fn retain() {
+    let mut list = (0..10).collect::<LinkedList<_>>();
+    let mut visited = Vec::new();
+    list.retain(|&n| {
+        visited.push(n);
+        n % 3 != 0
+    });
+    assert_eq!(visited, (0..10).collect::<Vec<_>>());
+    assert_list(&list, &[1, 2, 4, 5, 7, 8]);
+
+    list.retain(|_| false);
+    assert_list(&list, &[]);
+}
+
+// checks that rearranging the nodes drops every element exactly once
+#[test]
+fn drop_once_after_split_splice_sort_and_retain() {
+    use std::cell::Cell;
+
+    struct DropCounter<'a>(u32, &'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    let drops = Cell::new(0);
+    let mut list = (0..20)
+        .map(|n| DropCounter((n * 7) % 20, &drops))
+        .collect::<LinkedList<_>>();
+    let mut split = list.split_off(8);
+    split.sort_by_key(|counter| counter.0);
+    let mut cursor = list.cursor_front();
+    cursor.seek_forward(3);
+    cursor.splice_after(split);
+    list.retain(|counter| counter.0 % 4 != 0);
+    assert_eq!(drops.get(), 5);
+    drop(list);
+    assert_eq!(drops.get(), 20);
+}
@@ -0,0 +1,123 @@
+// Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom`:
+// loom runs each model under every interleaving of its threads.
+#![cfg(loom)]
+
+use doubly_linked_list::concurrent::Deque;
+use loom::sync::Arc;
+use loom::thread;
+
+fn deque_of(elements: &[i32]) -> Arc<Deque<i32>> {
+    let deque = Deque::new();
+    for &element in elements {
+        deque.push_back(element);
+    }
+    Arc::new(deque)
+}
+
+fn drain(deque: &Deque<i32>) -> Vec<i32> {
+    std::iter::from_fn(|| deque.pop_front()).collect()
+}
+
+#[test]
+fn loom_push_at_both_ends() {
+    loom::model(|| {
+        let deque = deque_of(&[]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.push_front(0));
+        deque.push_back(1);
+        thread.join().unwrap();
+        assert_eq!(drain(&deque), [0, 1]);
+    });
+}
+
+#[test]
+fn loom_push_back_twice() {
+    loom::model(|| {
+        let deque = deque_of(&[0]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.push_back(1));
+        deque.push_back(2);
+        thread.join().unwrap();
+        let elements = drain(&deque);
+        assert!(
+            elements == [0, 1, 2] || elements == [0, 2, 1],
+            "{:?}",
+            elements
+        );
+    });
+}
+
+#[test]
+fn loom_pop_both_ends_of_one_element() {
+    loom::model(|| {
+        let deque = deque_of(&[0]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.pop_front());
+        let back = deque.pop_back();
+        let front = thread.join().unwrap();
+        // exactly one of them gets it
+        assert_eq!(front.or(back), Some(0));
+        assert!(front.is_none() || back.is_none());
+        assert!(deque.is_empty());
+    });
+}
+
+#[test]
+fn loom_pop_both_ends_of_two_elements() {
+    loom::model(|| {
+        let deque = deque_of(&[0, 1]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.pop_front());
+        let back = deque.pop_back();
+        let front = thread.join().unwrap();
+        assert_eq!((front, back), (Some(0), Some(1)));
+        assert_eq!(deque.pop_back(), None);
+    });
+}
+
+#[test]
+fn loom_pop_back_twice() {
+    loom::model(|| {
+        let deque = deque_of(&[0, 1, 2]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.pop_back());
+        let mine = deque.pop_back().unwrap();
+        let theirs = thread.join().unwrap().unwrap();
+        assert_eq!(mine.max(theirs), 2);
+        assert_eq!(mine.min(theirs), 1);
+        assert_eq!(drain(&deque), [0]);
+    });
+}
+
+#[test]
+fn loom_push_front_while_popping_back() {
+    loom::model(|| {
+        let deque = deque_of(&[0]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.push_front(1));
+        let back = deque.pop_back();
+        thread.join().unwrap();
+        // the push may land before or after the pop
+        match back {
+            Some(0) => assert_eq!(drain(&deque), [1]),
+            Some(1) => unreachable!("pushed at the front, popped from the back"),
+            other => panic!("popped {:?}", other),
+        }
+    });
+}
+
+#[test]
+fn loom_push_back_while_popping_front_of_empty() {
+    loom::model(|| {
+        let deque = deque_of(&[]);
+        let other = Arc::clone(&deque);
+        let thread = thread::spawn(move || other.push_back(0));
+        let front = deque.pop_front();
+        thread.join().unwrap();
+        match front {
+            Some(0) => assert!(deque.is_empty()),
+            None => assert_eq!(drain(&deque), [0]),
+            other => panic!("popped {:?}", other),
+        }
+    });
+}
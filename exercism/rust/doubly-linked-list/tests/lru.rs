@@ -0,0 +1,176 @@
+use doubly_linked_list::lru::LruCache;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn keys<V>(cache: &LruCache<&'static str, V>) -> Vec<&'static str> {
+    cache.iter().map(|(&key, _)| key).collect()
+}
+
+// ———————————————————————————————————————————————————————————
+// Lookups and recency
+// ———————————————————————————————————————————————————————————
+
+#[test]
+fn lru_put_and_get() {
+    let mut cache = LruCache::new(2);
+    assert!(cache.is_empty());
+    assert_eq!(cache.put("a", 1), None);
+    assert_eq!(cache.put("b", 2), None);
+    assert_eq!(cache.get("a"), Some(&1));
+    assert_eq!(cache.get("c"), None);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(keys(&cache), ["a", "b"]);
+}
+
+#[test]
+fn lru_evicts_the_least_recently_used() {
+    let mut cache = LruCache::new(2);
+    cache.put("a", 1);
+    cache.put("b", 2);
+    cache.get("a");
+    cache.put("c", 3);
+    assert!(!cache.contains_key("b"));
+    assert_eq!(keys(&cache), ["c", "a"]);
+}
+
+#[test]
+fn lru_peek_does_not_mark_as_used() {
+    let mut cache = LruCache::new(2);
+    cache.put("a", 1);
+    cache.put("b", 2);
+    assert_eq!(cache.peek("a"), Some(&1));
+    cache.put("c", 3);
+    assert_eq!(cache.peek("a"), None);
+}
+
+#[test]
+fn lru_put_replaces_and_marks_as_used() {
+    let mut cache = LruCache::new(2);
+    cache.put("a", 1);
+    cache.put("b", 2);
+    assert_eq!(cache.put("a", 10), Some(1));
+    assert_eq!(cache.len(), 2);
+    cache.put("c", 3);
+    assert_eq!(keys(&cache), ["c", "a"]);
+    assert_eq!(cache.peek("a"), Some(&10));
+}
+
+#[test]
+fn lru_get_mut_and_remove() {
+    let mut cache = LruCache::new(3);
+    cache.put(String::from("a"), vec![1]);
+    cache.put(String::from("b"), vec![2]);
+    cache.get_mut("a").unwrap().push(10);
+    assert_eq!(cache.remove("a"), Some(vec![1, 10]));
+    assert_eq!(cache.remove("a"), None);
+    assert_eq!(cache.len(), 1);
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(cache.weight(), 0);
+}
+
+// ———————————————————————————————————————————————————————————
+// Limits and eviction
+// ———————————————————————————————————————————————————————————
+
+#[test]
+fn lru_zero_capacity_holds_nothing() {
+    let mut cache = LruCache::new(0);
+    cache.put("a", 1);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn lru_set_capacity_evicts() {
+    let mut cache = LruCache::new(4);
+    for (n, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+        cache.put(key, n);
+    }
+    cache.set_capacity(2);
+    assert_eq!(cache.capacity(), 2);
+    assert_eq!(keys(&cache), ["d", "c"]);
+}
+
+#[test]
+fn lru_evicts_by_weight() {
+    let mut cache = LruCache::new(100).with_max_weight(10, |_, value: &String| value.len());
+    cache.put("a", "xxxx".to_string());
+    cache.put("b", "xxxx".to_string());
+    assert_eq!(cache.weight(), 8);
+    cache.put("c", "xxx".to_string());
+    assert_eq!(keys(&cache), ["c", "b"]);
+    assert_eq!(cache.weight(), 7);
+
+    // growing an entry evicts the others
+    cache.put("c", "xxxxxxxx".to_string());
+    assert_eq!(keys(&cache), ["c"]);
+    assert_eq!(cache.weight(), 8);
+
+    // an entry too heavy on its own is evicted straight away
+    cache.put("d", "x".repeat(11));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn lru_calls_back_on_eviction_only() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&evicted);
+    let mut cache = LruCache::new(2).with_eviction_callback(move |key, value| {
+        log.lock().unwrap().push((key, value));
+    });
+
+    cache.put("a", 1);
+    cache.put("b", 2);
+    cache.put("a", 10);
+    cache.put("c", 3);
+    cache.remove("a");
+    cache.put("d", 4);
+    cache.put("e", 5);
+    assert_eq!(*evicted.lock().unwrap(), [("b", 2), ("c", 3)]);
+    assert_eq!(keys(&cache), ["e", "d"]);
+}
+
+#[test]
+fn lru_with_closures_moves_to_another_thread() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&evicted);
+    let mut cache = LruCache::new(4)
+        .with_max_weight(2, |_: &&str, _: &i32| 1)
+        .with_eviction_callback(move |key, _| log.lock().unwrap().push(key));
+    cache.put("a", 1);
+
+    let cache = thread::spawn(move || {
+        cache.put("b", 2);
+        cache.put("c", 3);
+        cache
+    })
+    .join()
+    .unwrap();
+    assert_eq!(*evicted.lock().unwrap(), ["a"]);
+    assert_eq!(keys(&cache), ["c", "b"]);
+}
+
+#[test]
+fn lru_stays_consistent_under_churn() {
+    let mut cache = LruCache::new(16);
+    for n in 0..1_000_u32 {
+        let key = n.wrapping_mul(2_654_435_761) % 40;
+        if n % 7 == 0 {
+            cache.remove(&key);
+        } else if cache.get(&key).is_none() {
+            cache.put(key, n);
+        }
+        assert!(cache.len() <= 16);
+        assert_eq!(cache.iter().count(), cache.len());
+        assert!(cache.iter().all(|(key, _)| cache.contains_key(key)));
+    }
+}
+
+#[test]
+fn lru_debug_from_most_recent() {
+    let mut cache = LruCache::new(2);
+    cache.put(1, 'a');
+    cache.put(2, 'b');
+    assert_eq!(format!("{:?}", cache), "{2: 'b', 1: 'a'}");
+}
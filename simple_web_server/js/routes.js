@@ -0,0 +1,9 @@
+// Highlight the route of a bus on the map while hovering over its timetable.
+document.querySelectorAll("[data-route]").forEach((timetable) => {
+  const polyline = document.getElementById(`route-${timetable.dataset.route}`);
+  if (polyline === null) {
+    return;
+  }
+  timetable.addEventListener("mouseenter", () => polyline.classList.add("highlighted"));
+  timetable.addEventListener("mouseleave", () => polyline.classList.remove("highlighted"));
+});
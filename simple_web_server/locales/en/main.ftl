@@ -0,0 +1,18 @@
+app-title = Flaxib Scolaire
+language-fr = Français
+language-en = English
+
+index-greeting = Hello { app-title }!
+
+routes-title = Routes of { $date }
+routes-geojson = Download as GeoJSON
+routes-stop = Stop
+routes-time = Time
+
+error-title = { $code ->
+    [404] Page not found
+    [422] Invalid request
+   *[other] Internal server error
+}
+error-request-id = Request ID: { $id }
+error-back = Back to the home page
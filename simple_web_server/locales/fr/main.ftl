@@ -0,0 +1,18 @@
+app-title = Flaxib Scolaire
+language-fr = Français
+language-en = English
+
+index-greeting = Bonjour { app-title } !
+
+routes-title = Itinéraires du { $date }
+routes-geojson = Télécharger au format GeoJSON
+routes-stop = Arrêt
+routes-time = Heure
+
+error-title = { $code ->
+    [404] Page introuvable
+    [422] Requête invalide
+   *[other] Erreur interne du serveur
+}
+error-request-id = Identifiant de la requête : { $id }
+error-back = Retour à l'accueil
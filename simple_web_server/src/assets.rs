@@ -0,0 +1,188 @@
+//! Static assets served from the `images/`, `css/` and `js/` directories.
+//!
+//! At launch every asset is hashed and published under a fingerprinted name,
+//! `css/style.css` becoming `css/style.<hash>.css`, which browsers may cache
+//! forever since its content can never change. Templates resolve those names
+//! with the `asset` Tera function: `{{ asset(path="css/style.css") }}`.
+//!
+//! Assets requested under their plain name are still served, but must be
+//! revalidated with their ETag on each use.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rocket::fs::NamedFile;
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder, Response};
+use rocket_dyn_templates::tera;
+use rocket_include_static_resources::{EntityTag, EtagIfNoneMatch};
+
+/// Directories, relative to the working directory, whose files are assets.
+pub const ASSET_DIRECTORIES: [&str; 3] = ["images", "css", "js"];
+
+/// Where the assets are mounted.
+pub const MOUNT_POINT: &str = "/assets";
+
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+const REVALIDATE: &str = "no-cache";
+
+struct Asset {
+    file: PathBuf,
+    etag: EntityTag<'static>,
+}
+
+/// Every asset found at launch, by plain and by fingerprinted name.
+pub struct AssetManifest {
+    assets: HashMap<String, Asset>,
+    fingerprinted: HashMap<String, String>,
+}
+
+impl AssetManifest {
+    /// Hash every file found in the `ASSET_DIRECTORIES` of `root`.
+    pub fn scan(root: &Path) -> io::Result<Self> {
+        let mut manifest = AssetManifest {
+            assets: HashMap::new(),
+            fingerprinted: HashMap::new(),
+        };
+        for directory in ASSET_DIRECTORIES {
+            let path = root.join(directory);
+            if path.is_dir() {
+                manifest.scan_directory(&path, directory)?;
+            }
+        }
+        Ok(manifest)
+    }
+
+    fn scan_directory(&mut self, directory: &Path, name: &str) -> io::Result<()> {
+        for entry in std::fs::read_dir(directory)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let file_name = match file_name.to_str() {
+                Some(file_name) => file_name,
+                None => continue,
+            };
+            let name = format!("{}/{}", name, file_name);
+            let file = entry.path();
+            if entry.file_type()?.is_dir() {
+                self.scan_directory(&file, &name)?;
+                continue;
+            }
+            let hash = fingerprint(&std::fs::read(&file)?);
+            let etag = EntityTag::with_string(false, hash.clone())
+                .expect("a hexadecimal fingerprint is a valid entity tag");
+            self.fingerprinted
+                .insert(fingerprinted_name(&name, &hash), name.clone());
+            self.assets.insert(name, Asset { file, etag });
+        }
+        Ok(())
+    }
+
+    /// The URL of the fingerprinted version of the asset `name`.
+    pub fn url(&self, name: &str) -> Option<String> {
+        let asset = self.assets.get(name)?;
+        Some(format!(
+            "{}/{}",
+            MOUNT_POINT,
+            fingerprinted_name(name, asset.etag.get_tag())
+        ))
+    }
+
+    /// Find the asset requested as `name`, together with how long it may be cached.
+    fn lookup(&self, name: &str) -> Option<(&Asset, &'static str)> {
+        match self.fingerprinted.get(name) {
+            Some(plain) => Some((&self.assets[plain], IMMUTABLE)),
+            None => self.assets.get(name).map(|asset| (asset, REVALIDATE)),
+        }
+    }
+
+    /// Respond with the asset requested as `name`, unless the client already
+    /// holds its current version.
+    pub async fn respond(
+        &self,
+        name: &str,
+        etag_if_none_match: &EtagIfNoneMatch<'_>,
+    ) -> Option<AssetResponse> {
+        let (asset, cache_control) = self.lookup(name)?;
+        let file = if etag_if_none_match.weak_eq(&asset.etag) {
+            None
+        } else {
+            Some(NamedFile::open(&asset.file).await.ok()?)
+        };
+        Some(AssetResponse {
+            file,
+            etag: asset.etag.clone(),
+            cache_control,
+        })
+    }
+}
+
+/// FNV-1a hash of `content`, which unlike `DefaultHasher` is stable across
+/// builds and thus keeps the fingerprints of unchanged assets.
+fn fingerprint(content: &[u8]) -> String {
+    let hash = content
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// Insert `hash` before the extension of `name`: `css/style.css` gives
+/// `css/style.<hash>.css`.
+fn fingerprinted_name(name: &str, hash: &str) -> String {
+    let file_start = name.rfind('/').map_or(0, |index| index + 1);
+    match name[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}.{}{}", &name[..dot], hash, &name[dot..])
+        }
+        _ => format!("{}.{}", name, hash),
+    }
+}
+
+/// The `asset` Tera function, giving the URL of an asset from its plain name.
+pub struct AssetFunction(pub Arc<AssetManifest>);
+
+impl tera::Function for AssetFunction {
+    fn call(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let path = match args.get("path").and_then(tera::Value::as_str) {
+            Some(path) => path,
+            None => return Err("`asset` expects a string `path` argument".into()),
+        };
+        self.0
+            .url(path)
+            .map(tera::Value::String)
+            .ok_or_else(|| format!("no asset named `{}`", path).into())
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// An asset file, or an empty `304 Not Modified` if the client already has it.
+pub struct AssetResponse {
+    file: Option<NamedFile>,
+    etag: EntityTag<'static>,
+    cache_control: &'static str,
+}
+
+impl<'r> Responder<'r, 'static> for AssetResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = match self.file {
+            Some(file) => Response::build_from(file.respond_to(request)?),
+            None => {
+                let mut response = Response::build();
+                response.status(Status::NotModified);
+                response
+            }
+        };
+        response
+            .raw_header("ETag", self.etag.to_string())
+            .raw_header("Cache-Control", self.cache_control)
+            .ok()
+    }
+}
@@ -0,0 +1,67 @@
+//! Error pages: HTML for the site, JSON for everything under `/api`.
+
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::serde::json::{json, Value};
+use rocket_dyn_templates::{context, Template};
+
+use crate::i18n::{Locale, LOCALES};
+use crate::logging::RequestId;
+
+async fn html(status: Status, request: &Request<'_>) -> Template {
+    let Locale(lang) = request
+        .guard::<Locale>()
+        .await
+        .succeeded()
+        .unwrap_or(Locale(LOCALES[0]));
+    let request_id = request.guard::<RequestId>().await.succeeded();
+    Template::render(
+        "error",
+        context! {
+            lang,
+            code: status.code,
+            request_id: request_id.map(|id| id.0),
+        },
+    )
+}
+
+async fn json(status: Status, request: &Request<'_>) -> Value {
+    let request_id = request.guard::<RequestId>().await.succeeded();
+    json!({
+        "error": {
+            "code": status.code,
+            "reason": status.reason(),
+            "request_id": request_id.map(|id| id.0),
+        }
+    })
+}
+
+#[catch(404)]
+pub async fn not_found(request: &Request<'_>) -> Template {
+    html(Status::NotFound, request).await
+}
+
+#[catch(422)]
+pub async fn unprocessable_entity(request: &Request<'_>) -> Template {
+    html(Status::UnprocessableEntity, request).await
+}
+
+#[catch(500)]
+pub async fn internal_error(request: &Request<'_>) -> Template {
+    html(Status::InternalServerError, request).await
+}
+
+#[catch(404)]
+pub async fn api_not_found(request: &Request<'_>) -> Value {
+    json(Status::NotFound, request).await
+}
+
+#[catch(422)]
+pub async fn api_unprocessable_entity(request: &Request<'_>) -> Value {
+    json(Status::UnprocessableEntity, request).await
+}
+
+#[catch(500)]
+pub async fn api_internal_error(request: &Request<'_>) -> Value {
+    json(Status::InternalServerError, request).await
+}
@@ -0,0 +1,46 @@
+//! Settings of the application, read with the rest of the Rocket
+//! configuration from `Rocket.toml` and `ROCKET_*` environment variables, as
+//! in `ROCKET_DATABASE_URL=file:///var/lib/flaxib/itineraries`. Those left
+//! out take their value from `AppConfig::default`.
+
+use chrono::NaiveTime;
+use rocket::serde::{Deserialize, Deserializer};
+
+use crate::itinerary::Position;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub struct AppConfig {
+    /// Where the itineraries are stored.
+    pub database_url: String,
+    /// Local times of day at which the scheduler runs.
+    pub scheduler_times: Vec<ScheduleTime>,
+    /// Where the buses start and end their routes.
+    pub depot: Position,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            database_url: "file://data/itineraries".into(),
+            scheduler_times: vec![ScheduleTime(NaiveTime::from_hms_opt(3, 0, 0).unwrap())],
+            depot: Position {
+                longitude: 4.832,
+                latitude: 45.758,
+            },
+        }
+    }
+}
+
+/// A time of day, written `HH:MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScheduleTime(pub NaiveTime);
+
+impl<'de> Deserialize<'de> for ScheduleTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&s, "%H:%M")
+            .map(ScheduleTime)
+            .map_err(|_| rocket::serde::de::Error::custom(format!("`{}` is not a HH:MM time", s)))
+    }
+}
@@ -0,0 +1,219 @@
+//! Translation of the templates with [Fluent](https://projectfluent.org/).
+//!
+//! Each locale has its catalogue in `locales/<locale>/main.ftl`. Templates look
+//! messages up with the `t` Tera function, given the `lang` of the request:
+//! `{{ t(key="routes-title", lang=lang, date=date) }}`, every other argument
+//! being passed to the message as a Fluent variable.
+//!
+//! The locale of a request is the one stored in the `lang` cookie if any, or
+//! else the best match for its `Accept-Language` header.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket_dyn_templates::tera;
+use unic_langid::LanguageIdentifier;
+
+/// Supported locales, the first one being the default.
+pub const LOCALES: [&str; 2] = ["fr", "en"];
+
+/// Cookie overriding the `Accept-Language` negotiation.
+pub const LOCALE_COOKIE: &str = "lang";
+
+/// The Fluent catalogues of every supported locale.
+pub struct Translations {
+    bundles: HashMap<&'static str, FluentBundle<FluentResource>>,
+}
+
+impl Translations {
+    /// Load the catalogue of every locale of `LOCALES` from `root`.
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let mut bundles = HashMap::new();
+        for locale in LOCALES {
+            let path = root.join(locale).join("main.ftl");
+            let source = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            let resource = FluentResource::try_new(source)
+                .map_err(|(_, errors)| format!("{}: {:?}", path.display(), errors))?;
+            let id: LanguageIdentifier = locale.parse().expect("supported locales are valid");
+            let mut bundle = FluentBundle::new_concurrent(vec![id]);
+            // Unicode isolation marks would end up verbatim in the HTML.
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .map_err(|errors| format!("{}: {:?}", path.display(), errors))?;
+            bundles.insert(locale, bundle);
+        }
+        Ok(Translations { bundles })
+    }
+
+    /// Format the message `key` of the catalogue of `locale`.
+    pub fn message(&self, locale: &str, key: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        let pattern = bundle.get_message(key)?.value()?;
+        let mut errors = vec![];
+        let message = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            warn_!("Message `{}` of `{}`: {:?}", key, locale, errors);
+        }
+        Some(message.into_owned())
+    }
+}
+
+/// Whether `locale` is one of the `LOCALES`, returning its static name.
+pub fn supported(locale: &str) -> Option<&'static str> {
+    LOCALES
+        .iter()
+        .copied()
+        .find(|supported| *supported == locale)
+}
+
+/// The supported locale best matching an `Accept-Language` header.
+pub fn negotiate(accept_language: &str) -> &'static str {
+    let requested = fluent_langneg::accepted_languages::parse(accept_language);
+    let available: Vec<LanguageIdentifier> = LOCALES
+        .iter()
+        .map(|locale| locale.parse().expect("supported locales are valid"))
+        .collect();
+    let default = available[0].clone();
+    let negotiated = negotiate_languages(
+        &requested,
+        &available,
+        Some(&default),
+        NegotiationStrategy::Lookup,
+    );
+    negotiated
+        .first()
+        .and_then(|locale| supported(locale.language.as_str()))
+        .unwrap_or(LOCALES[0])
+}
+
+/// The locale in which to answer a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale(pub &'static str);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Locale {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let from_cookie = request
+            .cookies()
+            .get(LOCALE_COOKIE)
+            .and_then(|cookie| supported(cookie.value()));
+        let locale = from_cookie.unwrap_or_else(|| {
+            request
+                .headers()
+                .get_one("Accept-Language")
+                .map_or(LOCALES[0], negotiate)
+        });
+        Outcome::Success(Locale(locale))
+    }
+}
+
+/// The `t` Tera function, formatting a message in the given `lang`.
+pub struct TranslateFunction(pub Arc<Translations>);
+
+impl tera::Function for TranslateFunction {
+    fn call(&self, args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+        let key = match args.get("key").and_then(tera::Value::as_str) {
+            Some(key) => key,
+            None => return Err("`t` expects a string `key` argument".into()),
+        };
+        let lang = args
+            .get("lang")
+            .and_then(tera::Value::as_str)
+            .unwrap_or(LOCALES[0]);
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            if name == "key" || name == "lang" {
+                continue;
+            }
+            match value {
+                tera::Value::String(s) => fluent_args.set(name.clone(), s.clone()),
+                tera::Value::Number(n) => {
+                    if let Some(n) = n.as_f64() {
+                        fluent_args.set(name.clone(), n)
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.0
+            .message(lang, key, Some(&fluent_args))
+            .map(tera::Value::String)
+            .ok_or_else(|| format!("no message `{}` in locale `{}`", key, lang).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Identifiers of the messages defined in the catalogue of `locale`.
+    fn message_ids(locale: &str) -> Vec<String> {
+        let source = std::fs::read_to_string(format!("locales/{}/main.ftl", locale)).unwrap();
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once('='))
+            .map(|(id, _)| id.trim().to_string())
+            .collect()
+    }
+
+    /// Keys looked up with `t(key="...")` in the templates.
+    fn template_keys() -> Vec<String> {
+        let mut keys = vec![];
+        for entry in std::fs::read_dir("templates").unwrap() {
+            let template = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let mut rest = template.as_str();
+            while let Some(start) = rest.find("t(key=\"") {
+                rest = &rest[start + "t(key=\"".len()..];
+                let end = rest.find('"').unwrap();
+                keys.push(rest[..end].to_string());
+                rest = &rest[end..];
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn catalogues_define_the_same_messages() {
+        let reference = message_ids(LOCALES[0]);
+        for locale in &LOCALES[1..] {
+            let ids = message_ids(locale);
+            let missing: Vec<_> = reference.iter().filter(|id| !ids.contains(id)).collect();
+            let extra: Vec<_> = ids.iter().filter(|id| !reference.contains(id)).collect();
+            assert!(missing.is_empty(), "missing in `{}`: {:?}", locale, missing);
+            assert!(extra.is_empty(), "only in `{}`: {:?}", locale, extra);
+        }
+    }
+
+    #[test]
+    fn templates_only_use_defined_messages() {
+        let translations = Translations::load(Path::new("locales")).unwrap();
+        for key in template_keys() {
+            for locale in LOCALES {
+                assert!(
+                    translations.bundles[locale].has_message(&key),
+                    "message `{}` is missing in `{}`",
+                    key,
+                    locale
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negotiate_accept_language() {
+        assert_eq!(negotiate("en-US,en;q=0.9,fr;q=0.8"), "en");
+        assert_eq!(negotiate("fr-FR,fr;q=0.9"), "fr");
+        assert_eq!(negotiate("de-DE"), "fr");
+        assert_eq!(negotiate(""), "fr");
+    }
+}
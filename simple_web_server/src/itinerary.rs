@@ -0,0 +1,265 @@
+//! Bus itineraries computed for a given day and their GeoJSON export.
+//!
+//! Itineraries are read from `<root>/<YYYY-MM-DD>.json`, one file per day, as
+//! written by the route computation. Each file holds the list of bus routes of
+//! that day, each route being the ordered list of its stops.
+
+use std::path::PathBuf;
+
+use rocket::request::FromParam;
+use rocket::serde::json::{self, json, Value};
+use rocket::serde::{Deserialize, Serialize};
+use rocket::time::{Date, Month};
+use rocket::tokio::fs;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Itinerary {
+    pub bus: String,
+    pub stops: Vec<Stop>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Stop {
+    pub name: String,
+    pub position: Position,
+    /// Scheduled passage time, formatted as `HH:MM`.
+    pub time: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Position {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+impl Position {
+    /// GeoJSON coordinates are `[longitude, latitude]`.
+    fn coordinates(&self) -> [f64; 2] {
+        [self.longitude, self.latitude]
+    }
+}
+
+/// A day of the itinerary calendar, parsed from a `YYYY-MM-DD` path segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItineraryDate(pub Date);
+
+impl ItineraryDate {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month: u8 = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        let month = Month::try_from(month).ok()?;
+        Date::from_calendar_date(year, month, day)
+            .ok()
+            .map(ItineraryDate)
+    }
+}
+
+impl std::fmt::Display for ItineraryDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}",
+            self.0.year(),
+            self.0.month() as u8,
+            self.0.day()
+        )
+    }
+}
+
+impl<'a> FromParam<'a> for ItineraryDate {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        ItineraryDate::parse(param).ok_or(param)
+    }
+}
+
+/// The `<date>.geojson` path segment of the GeoJSON export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeoJsonFile(pub ItineraryDate);
+
+impl<'a> FromParam<'a> for GeoJsonFile {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        param
+            .strip_suffix(".geojson")
+            .and_then(ItineraryDate::parse)
+            .map(GeoJsonFile)
+            .ok_or(param)
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(std::io::Error),
+    Format(json::serde_json::Error),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "cannot read itineraries: {}", e),
+            StoreError::Format(e) => write!(f, "malformed itineraries: {}", e),
+        }
+    }
+}
+
+/// Directory holding the computed itineraries, one JSON file per day.
+#[derive(Debug, Clone)]
+pub struct ItineraryStore {
+    root: PathBuf,
+}
+
+impl ItineraryStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ItineraryStore { root: root.into() }
+    }
+
+    /// Open the store at `url`, where only `file://<directory>` is supported.
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        url.strip_prefix("file://")
+            .map(ItineraryStore::new)
+            .ok_or_else(|| format!("unsupported database URL `{}`", url))
+    }
+
+    /// Check that the store can be read.
+    pub async fn check(&self) -> Result<(), String> {
+        fs::read_dir(&self.root)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", self.root.display(), e))
+    }
+
+    /// Load the itineraries of `date`, or `None` if none were computed that day.
+    pub async fn load(&self, date: ItineraryDate) -> Result<Option<Vec<Itinerary>>, StoreError> {
+        let path = self.root.join(format!("{}.json", date));
+        let content = match fs::read(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(StoreError::Io(e)),
+        };
+        json::from_slice(&content)
+            .map(Some)
+            .map_err(StoreError::Format)
+    }
+}
+
+/// Build a GeoJSON `FeatureCollection` with, for each itinerary, one
+/// `LineString` following its stops in order and one `Point` per stop, plus a
+/// `Point` for the depot.
+pub fn to_geojson(date: ItineraryDate, depot: Position, itineraries: &[Itinerary]) -> Value {
+    let mut features = vec![json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": depot.coordinates(),
+        },
+        "properties": {
+            "kind": "depot",
+        },
+    })];
+    for (index, itinerary) in itineraries.iter().enumerate() {
+        let route = index + 1;
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": itinerary
+                    .stops
+                    .iter()
+                    .map(|stop| stop.position.coordinates())
+                    .collect::<Vec<_>>(),
+            },
+            "properties": {
+                "kind": "route",
+                "route": route,
+                "bus": itinerary.bus,
+                "stop_count": itinerary.stops.len(),
+                "departure": itinerary.stops.first().map(|stop| &stop.time),
+                "arrival": itinerary.stops.last().map(|stop| &stop.time),
+            },
+        }));
+        for (order, stop) in itinerary.stops.iter().enumerate() {
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": stop.position.coordinates(),
+                },
+                "properties": {
+                    "kind": "stop",
+                    "route": route,
+                    "bus": itinerary.bus,
+                    "order": order + 1,
+                    "name": stop.name,
+                    "time": stop.time,
+                },
+            }));
+        }
+    }
+    json!({
+        "type": "FeatureCollection",
+        "name": format!("itineraries-{}", date),
+        "features": features,
+    })
+}
+
+/// Width and height of the SVG overview drawn on the routes page.
+const SVG_SIZE: (f64, f64) = (600.0, 400.0);
+
+/// Project every itinerary onto the SVG overview of the routes page, as the
+/// `points` attribute of an SVG `polyline`.
+///
+/// The projection is a plain equirectangular one fitted to the bounding box of
+/// all the stops, which is accurate enough at the scale of a school district
+/// and keeps the page free of any tile service.
+pub fn svg_polylines(itineraries: &[Itinerary]) -> Vec<String> {
+    let positions = itineraries
+        .iter()
+        .flat_map(|i| i.stops.iter().map(|s| s.position));
+    let (mut min, mut max) = (
+        Position {
+            longitude: f64::MAX,
+            latitude: f64::MAX,
+        },
+        Position {
+            longitude: f64::MIN,
+            latitude: f64::MIN,
+        },
+    );
+    for p in positions {
+        min.longitude = min.longitude.min(p.longitude);
+        min.latitude = min.latitude.min(p.latitude);
+        max.longitude = max.longitude.max(p.longitude);
+        max.latitude = max.latitude.max(p.latitude);
+    }
+    let span = (max.longitude - min.longitude).max(max.latitude - min.latitude);
+    let scale = if span > 0.0 {
+        (SVG_SIZE.0.min(SVG_SIZE.1) - 20.0) / span
+    } else {
+        0.0
+    };
+    itineraries
+        .iter()
+        .map(|itinerary| {
+            itinerary
+                .stops
+                .iter()
+                .map(|stop| {
+                    format!(
+                        "{:.1},{:.1}",
+                        10.0 + (stop.position.longitude - min.longitude) * scale,
+                        SVG_SIZE.1 - 10.0 - (stop.position.latitude - min.latitude) * scale,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
@@ -0,0 +1,100 @@
+//! Structured request logging: every response is logged as one JSON line on
+//! the standard output and carries an `X-Request-Id` header.
+//!
+//! The request ID is the one given by the client or a proxy in its own
+//! `X-Request-Id` header, or else a new one unique to this server process.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::json;
+use rocket::{Data, Response};
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// The ID of the request being handled, also usable as a request guard.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+struct RequestStart {
+    id: RequestId,
+    at: Instant,
+}
+
+pub struct RequestLogger {
+    prefix: String,
+    next_id: AtomicU64,
+}
+
+impl Default for RequestLogger {
+    fn default() -> Self {
+        let launch = chrono::Utc::now().timestamp();
+        RequestLogger {
+            prefix: format!("{:x}", launch),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl RequestLogger {
+    fn start(&self, request: &Request<'_>) -> RequestStart {
+        let id = match request.headers().get_one(REQUEST_ID_HEADER) {
+            Some(id) if !id.is_empty() && id.len() <= 128 => id.to_string(),
+            _ => format!(
+                "{}-{:x}",
+                self.prefix,
+                self.next_id.fetch_add(1, Ordering::Relaxed)
+            ),
+        };
+        RequestStart {
+            id: RequestId(id),
+            at: Instant::now(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger {
+    fn info(&self) -> Info {
+        Info {
+            name: "JSON request logger",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let start = self.start(request);
+        request.local_cache(|| start);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let start = request.local_cache(|| self.start(request));
+        response.set_raw_header(REQUEST_ID_HEADER, start.id.0.clone());
+        let line = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "request_id": start.id.0,
+            "method": request.method().as_str(),
+            "uri": request.uri().to_string(),
+            "status": response.status().code,
+            "duration_ms": start.at.elapsed().as_secs_f64() * 1000.0,
+            "remote": request.client_ip().map(|ip| ip.to_string()),
+            "user_agent": request.headers().get_one("User-Agent"),
+        });
+        println!("{}", line);
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let start = request.local_cache(|| RequestStart {
+            id: RequestId(String::new()),
+            at: Instant::now(),
+        });
+        Outcome::Success(start.id.clone())
+    }
+}
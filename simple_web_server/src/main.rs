@@ -6,33 +6,210 @@ extern crate rocket_include_static_resources;
 
 // use rocket_include_static_resources::{EtagIfNoneMatch, StaticContextManager, StaticResponse};
 
+mod assets;
+mod catchers;
+mod config;
+mod i18n;
+mod itinerary;
+mod logging;
+mod scheduler;
+#[cfg(test)]
+mod tests;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rocket::http::{ContentType, Cookie, CookieJar, Status};
+use rocket::response::Redirect;
+use rocket::serde::json::{json, Value};
+use rocket::{Build, Rocket, State};
 use rocket_dyn_templates::context;
 use rocket_dyn_templates::Template;
+use rocket_include_static_resources::EtagIfNoneMatch;
+
+use assets::{AssetFunction, AssetManifest, AssetResponse};
+use config::AppConfig;
+use i18n::{Locale, TranslateFunction, Translations};
+use itinerary::{GeoJsonFile, Itinerary, ItineraryDate, ItineraryStore};
+use logging::{RequestId, RequestLogger};
+use scheduler::Scheduler;
 
 static_response_handler! {
     "/favicon.ico" => favicon => "favicon",
 }
 
 #[get("/")]
-fn index() -> Template {
+fn index(locale: Locale) -> Template {
     Template::render(
         "index",
         context! {
             foo: 123,
-            title: "Flaxib Scolaire"
+            lang: locale.0,
         },
     )
 }
 
+#[get("/lang/<locale>")]
+fn set_locale(locale: &str, cookies: &CookieJar<'_>) -> Option<Redirect> {
+    let locale = i18n::supported(locale)?;
+    cookies.add(Cookie::new(i18n::LOCALE_COOKIE, locale));
+    Some(Redirect::to(uri!(index)))
+}
+
+#[get("/<path..>")]
+async fn asset(
+    path: PathBuf,
+    manifest: &State<Arc<AssetManifest>>,
+    etag_if_none_match: EtagIfNoneMatch<'_>,
+) -> Option<AssetResponse> {
+    let name = path
+        .iter()
+        .map(|segment| segment.to_str())
+        .collect::<Option<Vec<_>>>()?
+        .join("/");
+    manifest.respond(&name, &etag_if_none_match).await
+}
+
+async fn load_itineraries(
+    store: &ItineraryStore,
+    date: ItineraryDate,
+    request_id: &RequestId,
+) -> Result<Vec<Itinerary>, Status> {
+    match store.load(date).await {
+        Ok(Some(itineraries)) => Ok(itineraries),
+        Ok(None) => Err(Status::NotFound),
+        Err(e) => {
+            error_!("[{}] Itineraries of {}: {}", request_id.0, date, e);
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[get("/routes/<date>")]
+async fn routes_page(
+    date: ItineraryDate,
+    locale: Locale,
+    store: &State<ItineraryStore>,
+    request_id: RequestId,
+) -> Result<Template, Status> {
+    let itineraries = load_itineraries(store, date, &request_id).await?;
+    Ok(Template::render(
+        "routes",
+        context! {
+            lang: locale.0,
+            date: date.to_string(),
+            polylines: itinerary::svg_polylines(&itineraries),
+            itineraries,
+        },
+    ))
+}
+
+#[get("/api/routes/<file>")]
+async fn routes_geojson(
+    file: GeoJsonFile,
+    store: &State<ItineraryStore>,
+    config: &State<AppConfig>,
+    request_id: RequestId,
+) -> Result<(ContentType, Value), Status> {
+    let GeoJsonFile(date) = file;
+    let itineraries = load_itineraries(store, date, &request_id).await?;
+    Ok((
+        ContentType::new("application", "geo+json"),
+        itinerary::to_geojson(date, config.depot, &itineraries),
+    ))
+}
+
+/// Liveness: the server answers requests.
+#[get("/healthz")]
+fn healthz() -> Value {
+    json!({ "status": "ok" })
+}
+
+/// Readiness: the itineraries can be read and the scheduler is running.
+#[get("/readyz")]
+async fn readyz(
+    store: &State<ItineraryStore>,
+    scheduler: &State<Arc<Scheduler>>,
+) -> (Status, Value) {
+    let database = store.check().await.map(|_| "ok".to_string());
+    let scheduler = scheduler.check();
+    let ready = database.is_ok() && scheduler.is_ok();
+    let report = |check: Result<String, String>| match check {
+        Ok(detail) => json!({ "status": "ok", "detail": detail }),
+        Err(error) => json!({ "status": "error", "detail": error }),
+    };
+    (
+        if ready {
+            Status::Ok
+        } else {
+            Status::ServiceUnavailable
+        },
+        json!({
+            "status": if ready { "ready" } else { "unavailable" },
+            "checks": {
+                "database": report(database),
+                "scheduler": report(scheduler),
+            },
+        }),
+    )
+}
+
 #[launch]
 fn rocket() -> _ {
-    rocket::build()
+    app(rocket::build())
+}
+
+/// Set up the application on top of `rocket`, whose figment holds the configuration.
+fn app(rocket: Rocket<Build>) -> Rocket<Build> {
+    let config: AppConfig = rocket
+        .figment()
+        .extract()
+        .expect("invalid application configuration");
+    let store = ItineraryStore::from_url(&config.database_url).expect("invalid `database_url`");
+    let scheduler = Arc::new(Scheduler::new(config.scheduler_times.clone()));
+
+    let assets = Arc::new(AssetManifest::scan(Path::new(".")).expect("cannot read the assets"));
+    let asset_function = assets.clone();
+    let translations =
+        Arc::new(Translations::load(Path::new("locales")).expect("cannot load the translations"));
+
+    rocket
+        .attach(RequestLogger::default())
+        .attach(Scheduler::fairing(scheduler.clone(), store.clone()))
         .attach(static_resources_initializer!(
             "favicon" => "images/favicon.ico",
-            // "favicon-png" => "examples/front-end/images/favicon-16.png",
-            // "html-readme" => ("examples", "front-end", "html", "README.html"),
         ))
-        .attach(Template::fairing())
+        .attach(Template::custom(move |engines| {
+            engines
+                .tera
+                .register_function("asset", AssetFunction(asset_function.clone()));
+            engines
+                .tera
+                .register_function("t", TranslateFunction(translations.clone()));
+        }))
+        .manage(assets)
+        .manage(config)
+        .manage(store)
+        .manage(scheduler)
         .mount("/", routes![favicon])
-        .mount("/", routes![index])
+        .mount(assets::MOUNT_POINT, routes![asset])
+        .mount("/", routes![index, set_locale])
+        .mount("/", routes![routes_page, routes_geojson])
+        .mount("/", routes![healthz, readyz])
+        .register(
+            "/",
+            catchers![
+                catchers::not_found,
+                catchers::unprocessable_entity,
+                catchers::internal_error
+            ],
+        )
+        .register(
+            "/api",
+            catchers![
+                catchers::api_not_found,
+                catchers::api_unprocessable_entity,
+                catchers::api_internal_error
+            ],
+        )
 }
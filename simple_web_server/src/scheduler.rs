@@ -0,0 +1,122 @@
+//! Daily job run at the `scheduler_times` of the configuration.
+//!
+//! The job checks that the itineraries of the next day are available and
+//! readable, so that a missing computation is noticed the night before.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+use rocket::fairing::{AdHoc, Fairing};
+
+use crate::config::ScheduleTime;
+use crate::itinerary::{ItineraryDate, ItineraryStore};
+
+pub struct Scheduler {
+    times: Vec<ScheduleTime>,
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    running: bool,
+    last_run: Option<DateTime<Local>>,
+}
+
+/// Marks the scheduler as running for as long as its task is alive, including
+/// when the task panics.
+struct RunningGuard(Arc<Scheduler>);
+
+impl RunningGuard {
+    fn new(scheduler: Arc<Scheduler>) -> Self {
+        scheduler.state.lock().unwrap().running = true;
+        RunningGuard(scheduler)
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.state.lock() {
+            state.running = false;
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn new(times: Vec<ScheduleTime>) -> Self {
+        Scheduler {
+            times,
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    /// Spawn the scheduler task once Rocket has launched.
+    pub fn fairing(scheduler: Arc<Scheduler>, store: ItineraryStore) -> impl Fairing {
+        AdHoc::on_liftoff("Scheduler", move |_| {
+            Box::pin(async move {
+                if scheduler.times.is_empty() {
+                    info_!("Scheduler disabled: no `scheduler_times` configured.");
+                    return;
+                }
+                let running = RunningGuard::new(scheduler.clone());
+                rocket::tokio::spawn(async move {
+                    let _running = running;
+                    scheduler.run(store).await
+                });
+            })
+        })
+    }
+
+    /// The first scheduled time strictly after `now`.
+    pub fn next_run(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let today = now.date_naive();
+        [today, today + Duration::days(1)]
+            .iter()
+            .flat_map(|day| {
+                self.times.iter().filter_map(move |time| {
+                    Local.from_local_datetime(&day.and_time(time.0)).earliest()
+                })
+            })
+            .filter(|run| *run > now)
+            .min()
+    }
+
+    /// Whether the scheduler is healthy, with a description of its state.
+    pub fn check(&self) -> Result<String, String> {
+        let state = self
+            .state
+            .lock()
+            .map_err(|_| "scheduler state poisoned".to_string())?;
+        match (self.times.is_empty(), state.running, state.last_run) {
+            (true, _, _) => Ok("disabled".into()),
+            (false, false, _) => Err("scheduler task is not running".into()),
+            (false, true, Some(last_run)) => Ok(format!("last run at {}", last_run.to_rfc3339())),
+            (false, true, None) => Ok("waiting for first run".into()),
+        }
+    }
+
+    async fn run(&self, store: ItineraryStore) {
+        loop {
+            let now = Local::now();
+            let next = match self.next_run(now) {
+                Some(next) => next,
+                None => return,
+            };
+            let delay = (next - now).to_std().unwrap_or_default();
+            rocket::tokio::time::sleep(delay).await;
+            check_tomorrow(&store).await;
+            self.state.lock().unwrap().last_run = Some(Local::now());
+        }
+    }
+}
+
+async fn check_tomorrow(store: &ItineraryStore) {
+    let tomorrow = (Local::now() + Duration::days(1)).format("%Y-%m-%d");
+    let date = ItineraryDate::parse(&tomorrow.to_string()).expect("chrono formats valid dates");
+    match store.load(date).await {
+        Ok(Some(itineraries)) => {
+            info_!("Scheduler: {} itineraries for {}.", itineraries.len(), date)
+        }
+        Ok(None) => warn_!("Scheduler: no itineraries computed for {}.", date),
+        Err(e) => error_!("Scheduler: itineraries of {}: {}", date, e),
+    }
+}
@@ -0,0 +1,300 @@
+use rocket::http::{ContentType, Cookie, Header, Status};
+use rocket::local::blocking::{Client, LocalResponse};
+use rocket::serde::json::{json, Value};
+use rocket::Config;
+
+use super::app;
+
+const DATE: &str = "2022-03-01";
+
+fn client() -> Client {
+    Client::tracked(app(rocket::build())).expect("valid rocket instance")
+}
+
+/// A client reading its itineraries from `database_url` instead.
+fn client_with_database(database_url: &str) -> Client {
+    let figment = Config::figment().merge(("database_url", database_url));
+    Client::tracked(app(rocket::custom(figment))).expect("valid rocket instance")
+}
+
+fn json_body(response: LocalResponse<'_>) -> Value {
+    response.into_json().expect("a JSON body")
+}
+
+#[test]
+fn index_renders_template() {
+    let client = client();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    let body = response.into_string().unwrap();
+    assert!(body.contains(r#"<html lang="fr">"#));
+    assert!(body.contains("Bonjour Flaxib Scolaire !"));
+    assert!(body.contains(r#"href="/assets/css/style."#));
+}
+
+#[test]
+fn index_follows_accept_language() {
+    let client = client();
+    let response = client
+        .get("/")
+        .header(Header::new("Accept-Language", "en-GB,en;q=0.9"))
+        .dispatch();
+    assert!(response
+        .into_string()
+        .unwrap()
+        .contains("Hello Flaxib Scolaire!"));
+}
+
+#[test]
+fn locale_cookie_overrides_accept_language() {
+    let client = client();
+    let response = client.get("/lang/en").dispatch();
+    assert_eq!(response.status(), Status::SeeOther);
+    assert_eq!(response.headers().get_one("Location"), Some("/"));
+    assert_eq!(
+        response.cookies().get("lang").map(|c| c.value()),
+        Some("en")
+    );
+
+    let response = client
+        .get("/")
+        .header(Header::new("Accept-Language", "fr"))
+        .cookie(Cookie::new("lang", "en"))
+        .dispatch();
+    assert!(response
+        .into_string()
+        .unwrap()
+        .contains("Hello Flaxib Scolaire!"));
+
+    assert_eq!(client.get("/lang/de").dispatch().status(), Status::NotFound);
+}
+
+#[test]
+fn favicon_honours_etag() {
+    let client = client();
+    let response = client.get("/favicon.ico").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+    assert!(!response.into_bytes().unwrap().is_empty());
+
+    let response = client
+        .get("/favicon.ico")
+        .header(Header::new("If-None-Match", etag))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotModified);
+    assert!(response.into_bytes().unwrap_or_default().is_empty());
+}
+
+#[test]
+fn fingerprinted_asset_is_immutable() {
+    let client = client();
+    let body = client.get("/").dispatch().into_string().unwrap();
+    let start = body.find("/assets/css/style.").unwrap();
+    let url = &body[start..start + body[start..].find('"').unwrap()];
+
+    let response = client.get(url).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::CSS));
+    assert_eq!(
+        response.headers().get_one("Cache-Control"),
+        Some("public, max-age=31536000, immutable")
+    );
+    let etag = response.headers().get_one("ETag").unwrap().to_string();
+
+    let response = client
+        .get(url)
+        .header(Header::new("If-None-Match", etag))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotModified);
+
+    let response = client.get("/assets/css/style.css").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Cache-Control"),
+        Some("no-cache")
+    );
+}
+
+#[test]
+fn routes_page_lists_itineraries() {
+    let client = client();
+    let response = client.get(format!("/routes/{}", DATE)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body = response.into_string().unwrap();
+    assert!(body.contains("Itinéraires du 2022-03-01"));
+    assert!(body.contains("Bus 1"));
+    assert!(body.contains("Lotissement des Tilleuls"));
+    assert_eq!(body.matches("<polyline").count(), 2);
+}
+
+#[test]
+fn routes_geojson_export() {
+    let client = client();
+    let response = client
+        .get(format!("/api/routes/{}.geojson", DATE))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type(),
+        Some(ContentType::new("application", "geo+json"))
+    );
+    let geojson = json_body(response);
+    assert_eq!(geojson["type"], "FeatureCollection");
+    let features = geojson["features"].as_array().unwrap();
+    // The depot, then for each of the two buses its route and four stops.
+    assert_eq!(features.len(), 1 + 2 * (1 + 4));
+    assert_eq!(features[0]["properties"]["kind"], "depot");
+    assert_eq!(features[1]["geometry"]["type"], "LineString");
+    assert_eq!(features[1]["properties"]["departure"], "07:20");
+    assert_eq!(features[1]["properties"]["arrival"], "07:45");
+    assert_eq!(features[2]["geometry"]["type"], "Point");
+    assert_eq!(features[2]["properties"]["order"], 1);
+    assert_eq!(
+        features[2]["geometry"]["coordinates"],
+        json!([4.832, 45.758])
+    );
+}
+
+#[test]
+fn unknown_day_is_not_found() {
+    let client = client();
+    let response = client.get("/routes/2022-03-02").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+
+    let response = client.get("/api/routes/2022-03-02.geojson").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(json_body(response)["error"]["code"], 404);
+
+    let response = client.get("/api/routes/2022-02-30.geojson").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn html_not_found_catcher() {
+    let client = client();
+    let response = client
+        .get("/nowhere")
+        .header(Header::new("X-Request-Id", "test-404"))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    let body = response.into_string().unwrap();
+    assert!(body.contains("Page introuvable"));
+    assert!(body.contains("test-404"));
+}
+
+#[test]
+fn json_not_found_catcher() {
+    let client = client();
+    let response = client
+        .get("/api/nowhere")
+        .header(Header::new("X-Request-Id", "test-404"))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+    assert_eq!(
+        json_body(response),
+        json!({
+            "error": {
+                "code": 404,
+                "reason": "Not Found",
+                "request_id": "test-404",
+            }
+        })
+    );
+}
+
+/// A route with a data guard, to trigger the `422 Unprocessable Entity` catchers.
+// Rocket's codegen emits an unused `uri!` macro re-export next to each route.
+#[allow(unused_imports)]
+mod payload {
+    use rocket::serde::json::Json;
+
+    #[post("/", data = "<n>")]
+    pub fn number(n: Json<u64>) -> String {
+        n.to_string()
+    }
+}
+
+#[test]
+fn unprocessable_entity_catchers() {
+    let rocket = app(rocket::build())
+        .mount("/number", routes![payload::number])
+        .mount("/api/number", routes![payload::number]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let response = client
+        .post("/number")
+        .header(ContentType::JSON)
+        .body("-1")
+        .dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    assert!(response.into_string().unwrap().contains("Requête invalide"));
+
+    let response = client
+        .post("/api/number")
+        .header(ContentType::JSON)
+        .body("-1")
+        .dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(json_body(response)["error"]["code"], 422);
+}
+
+#[test]
+fn internal_error_catchers() {
+    // The fixture of that day is not valid JSON.
+    let client = client_with_database("file://tests/fixtures/itineraries");
+    let response = client.get("/routes/1999-12-31").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert!(response
+        .into_string()
+        .unwrap()
+        .contains("Erreur interne du serveur"));
+
+    let response = client.get("/api/routes/1999-12-31.geojson").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(json_body(response)["error"]["code"], 500);
+}
+
+#[test]
+fn health_endpoints() {
+    let client = client();
+    let response = client.get("/healthz").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(json_body(response), json!({ "status": "ok" }));
+
+    let response = client.get("/readyz").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let report = json_body(response);
+    assert_eq!(report["status"], "ready");
+    assert_eq!(report["checks"]["database"]["status"], "ok");
+}
+
+#[test]
+fn readiness_fails_without_database() {
+    let client = client_with_database("file://tests/fixtures/missing");
+    let response = client.get("/readyz").dispatch();
+    assert_eq!(response.status(), Status::ServiceUnavailable);
+    let report = json_body(response);
+    assert_eq!(report["status"], "unavailable");
+    assert_eq!(report["checks"]["database"]["status"], "error");
+}
+
+#[test]
+fn responses_carry_request_id() {
+    let client = client();
+    let response = client
+        .get("/healthz")
+        .header(Header::new("X-Request-Id", "abc-123"))
+        .dispatch();
+    assert_eq!(response.headers().get_one("X-Request-Id"), Some("abc-123"));
+
+    let first = client.get("/healthz").dispatch();
+    let second = client.get("/healthz").dispatch();
+    let first = first.headers().get_one("X-Request-Id").unwrap();
+    let second = second.headers().get_one("X-Request-Id").unwrap();
+    assert_ne!(first, second);
+}